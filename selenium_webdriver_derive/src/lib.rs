@@ -0,0 +1,218 @@
+//! The PageObject derive macro for the selenium_webdriver crate.
+//!
+//! Use it through the "derive" feature of selenium_webdriver, see the PageObject docs there.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericArgument, LitStr, PathArguments,
+    Type,
+};
+
+///Generates the PageObject impl and a lazy accessor for every #[find] and #[find_all] field
+///
+/// The field marked with #[context] holds the Browser, the Element or any other SearchContext.
+/// The #[find(...)] fields must be Lazy<T>, where T is the Element or a page object with the Element context,
+/// the #[find_all(...)] ones must be Lazy<Vec<T>>. The accessor has the name and the visibility of the field.
+/// The locator is one of css, xpath, link_text, partial_link_text, tag_name, id, name, class_name and test_id.
+/// The other fields are filled with Default::default().
+#[proc_macro_derive(PageObject, attributes(context, find, find_all))]
+pub fn derive_page_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+enum FieldKind {
+    Context,
+    Find(TokenStream2),
+    FindAll(TokenStream2),
+    Other,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new(input.span(), "PageObject needs named fields")),
+        },
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "PageObject can only be derived for structs",
+            ))
+        }
+    };
+    let mut context = None;
+    let mut inits = vec![];
+    let mut accessors = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        match field_kind(field)? {
+            FieldKind::Context => {
+                if context.is_some() {
+                    return Err(Error::new(
+                        field.span(),
+                        "only one field may be the #[context]",
+                    ));
+                }
+                context = Some((ident, &field.ty));
+                inits.push(quote! { #ident: context });
+            }
+            FieldKind::Find(locator) => {
+                let vis = &field.vis;
+                let target = lazy_target(&field.ty)?;
+                inits.push(quote! { #ident: ::selenium_webdriver::Lazy::new() });
+                accessors.push(quote! {
+                    #vis fn #ident(&self) -> ::std::result::Result<#target, ::selenium_webdriver::WebDriverError> {
+                        let element = ::selenium_webdriver::SearchContext::find_element(
+                            ::selenium_webdriver::PageObject::context(self),
+                            #locator,
+                        )?;
+                        ::std::result::Result::Ok(<#target as ::selenium_webdriver::FromElement>::from_element(element))
+                    }
+                });
+            }
+            FieldKind::FindAll(locator) => {
+                let vis = &field.vis;
+                let target = lazy_target(&field.ty)?;
+                let item = vec_item(target)?;
+                inits.push(quote! { #ident: ::selenium_webdriver::Lazy::new() });
+                accessors.push(quote! {
+                    #vis fn #ident(&self) -> ::std::result::Result<#target, ::selenium_webdriver::WebDriverError> {
+                        let elements = ::selenium_webdriver::SearchContext::find_elements(
+                            ::selenium_webdriver::PageObject::context(self),
+                            #locator,
+                        )?;
+                        ::std::result::Result::Ok(elements
+                            .into_iter()
+                            .map(<#item as ::selenium_webdriver::FromElement>::from_element)
+                            .collect())
+                    }
+                });
+            }
+            FieldKind::Other => inits.push(quote! { #ident: ::std::default::Default::default() }),
+        }
+    }
+    let (context_ident, context_ty) = match context {
+        Some(context) => context,
+        None => {
+            return Err(Error::new(
+                input.span(),
+                "PageObject needs a #[context] field",
+            ))
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::selenium_webdriver::PageObject for #name #ty_generics #where_clause {
+            type Context = #context_ty;
+            fn from_context(context: Self::Context) -> Self {
+                #name { #(#inits),* }
+            }
+            fn context(&self) -> &Self::Context {
+                &self.#context_ident
+            }
+        }
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#accessors)*
+        }
+    })
+}
+
+fn field_kind(field: &syn::Field) -> Result<FieldKind, Error> {
+    let mut kind = FieldKind::Other;
+    for attr in &field.attrs {
+        let new_kind = if attr.path().is_ident("context") {
+            FieldKind::Context
+        } else if attr.path().is_ident("find") {
+            FieldKind::Find(parse_locator(attr)?)
+        } else if attr.path().is_ident("find_all") {
+            FieldKind::FindAll(parse_locator(attr)?)
+        } else {
+            continue;
+        };
+        if !matches!(kind, FieldKind::Other) {
+            return Err(Error::new(
+                attr.span(),
+                "a field may have only one of #[context], #[find] and #[find_all]",
+            ));
+        }
+        kind = new_kind;
+    }
+    Ok(kind)
+}
+
+fn parse_locator(attr: &syn::Attribute) -> Result<TokenStream2, Error> {
+    let mut locator = None;
+    attr.parse_nested_meta(|meta| {
+        if locator.is_some() {
+            return Err(meta.error("only one locator may be given"));
+        }
+        let key = meta
+            .path
+            .get_ident()
+            .map(|i| i.to_string())
+            .unwrap_or_default();
+        let value: LitStr = meta.value()?.parse()?;
+        let strategy = match key.as_str() {
+            "css" => quote! { ::selenium_webdriver::LocatorStrategy::CSS(#value) },
+            "xpath" => quote! { ::selenium_webdriver::LocatorStrategy::XPATH(#value) },
+            "link_text" => quote! { ::selenium_webdriver::LocatorStrategy::LINKTEXT(#value) },
+            "partial_link_text" => {
+                quote! { ::selenium_webdriver::LocatorStrategy::PARTLINKTEXT(#value) }
+            }
+            "tag_name" => quote! { ::selenium_webdriver::LocatorStrategy::TAGNAME(#value) },
+            "id" => quote! { ::selenium_webdriver::By::id(#value) },
+            "name" => quote! { ::selenium_webdriver::By::name(#value) },
+            "class_name" => quote! { ::selenium_webdriver::By::class_name(#value) },
+            "test_id" => quote! { ::selenium_webdriver::By::test_id(#value) },
+            _ => return Err(meta.error("unknown locator, expected one of css, xpath, link_text, partial_link_text, tag_name, id, name, class_name, test_id")),
+        };
+        locator = Some(strategy);
+        Ok(())
+    })?;
+    locator.ok_or_else(|| {
+        Error::new(
+            attr.span(),
+            "a locator is expected, e.g. #[find(css = \"...\")]",
+        )
+    })
+}
+
+//Returns the T of the Lazy<T>
+fn lazy_target(ty: &Type) -> Result<&Type, Error> {
+    generic_arg(ty, "Lazy").ok_or_else(|| {
+        Error::new(
+            ty.span(),
+            "the #[find] and #[find_all] fields must be Lazy<T>",
+        )
+    })
+}
+
+//Returns the T of the Vec<T>
+fn vec_item(ty: &Type) -> Result<&Type, Error> {
+    generic_arg(ty, "Vec")
+        .ok_or_else(|| Error::new(ty.span(), "the #[find_all] fields must be Lazy<Vec<T>>"))
+}
+
+fn generic_arg<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
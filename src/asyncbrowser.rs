@@ -0,0 +1,599 @@
+use super::actions::*;
+use super::asyncelement::*;
+use super::asynctransport::*;
+use super::browser::utils::*;
+use super::browser::*;
+use super::capabilities::*;
+use super::chromeoptions::*;
+use super::error::*;
+use super::firefoxoptions::*;
+use super::reqs::*;
+use super::safarioptions::*;
+use super::script::*;
+use super::serverurl::*;
+use super::transport::*;
+use super::wirelog::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+///The async counterpart of the Browser, available with the "async" feature
+///
+/// All the requests go through a non-blocking transport, so many sessions can be driven
+/// concurrently from one tokio runtime. The methods mirror the ones of the Browser.
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// # async fn run() -> Result<(), WebDriverError> {
+/// let mut br = AsyncBrowser::start_session(BrowserName::Chrome, vec!["--headless"]).await?;
+/// br.open("https://vk.com").await?;
+/// let el = br.find_element(LocatorStrategy::CSS("#ts_input")).await?;
+/// el.send_keys("Sup!").await?;
+/// br.close_browser().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncBrowser {
+    transport: Arc<SessionTransport<dyn AsyncTransport>>,
+    session_url: String,
+    capabilities: SessionCapabilities,
+    detached: bool,
+}
+
+impl AsyncBrowser {
+    ///Starts the basic session on the local selenium server, see Browser::start_session
+    pub async fn start_session(
+        browser: BrowserName,
+        args: Vec<&str>,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        let body = create_session_body_json(browser, args);
+        async_session_at(&ServerUrl::default(), &body, None).await
+    }
+    pub async fn start_session_with_capabilities(
+        capabilities: Capabilities,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        async_session_at(
+            &ServerUrl::default(),
+            &capabilities.cap_string,
+            capabilities.observer,
+        )
+        .await
+    }
+    pub async fn start_remote_session_with_capabilities(
+        capabilities: Capabilities,
+        ip: &str,
+        port: &str,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        async_session_at(
+            &remote_url(ip, port)?,
+            &capabilities.cap_string,
+            capabilities.observer,
+        )
+        .await
+    }
+    ///Creates the session using a custom transport, e.g. the MockTransport
+    pub async fn start_session_with_transport<T: AsyncTransport + 'static>(
+        transport: T,
+        capabilities: Capabilities,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        new_async_session(
+            Arc::new(transport),
+            &ServerUrl::default(),
+            &capabilities.cap_string,
+            capabilities.observer,
+        )
+        .await
+    }
+    ///Creates the session on the server at the given url, see Browser::start_session_with_url
+    pub async fn start_session_with_url(
+        url: &ServerUrl,
+        capabilities: Capabilities,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        async_session_at(url, &capabilities.cap_string, capabilities.observer).await
+    }
+    pub async fn start_remote_session(
+        browser: BrowserName,
+        platform: &str,
+        ip: &str,
+        port: &str,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        let body = create_remote_session_body(browser, platform);
+        async_session_at(&remote_url(ip, port)?, &body, None).await
+    }
+    pub async fn start_chrome_session_with_options(
+        options: ChromeOptions,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        let body = create_json_body_for_session_with_chrome_options(options);
+        async_session_at(&ServerUrl::default(), &body, None).await
+    }
+    pub async fn start_firefox_session_with_options(
+        options: FirefoxOptions,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        let body = create_json_body_for_session_with_firefox_options(options);
+        async_session_at(&ServerUrl::default(), &body, None).await
+    }
+    pub async fn start_safari_session_with_options(
+        options: SafariOptions,
+    ) -> Result<AsyncBrowser, WebDriverError> {
+        let body = create_json_body_for_session_with_safari_options(options);
+        async_session_at(&ServerUrl::default(), &body, None).await
+    }
+    async fn command(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: &str,
+    ) -> Result<serde_json::Value, WebDriverError> {
+        let url = format!("{}/{}", self.session_url, endpoint);
+        send_command_async(&*self.transport, method, &url, body).await
+    }
+    fn element(&self, reference: serde_json::Value) -> Result<AsyncElement, WebDriverError> {
+        let transport: Arc<dyn AsyncTransport> = self.transport.clone();
+        AsyncElement::from_reference(&transport, &self.session_url, reference)
+    }
+    ///Open a webpage or a local file
+    pub async fn open(&self, uri: &str) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "url": uri }).to_string();
+        self.command(Method::POST, "url", &body).await?;
+        Ok(())
+    }
+    ///Get the url of the current page.
+    pub async fn get_link(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "url", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Deletes current session, after that all the commands return WebDriverError::SessionClosed
+    ///
+    /// If the AsyncBrowser is dropped inside a tokio runtime without calling this method,
+    /// the session is deleted by a task spawned in drop(), unless the browser was detached.
+    pub async fn close_browser(&mut self) -> Result<(), WebDriverError> {
+        send_command_async(&*self.transport, Method::DELETE, &self.session_url, "").await?;
+        self.transport.close();
+        self.session_url = String::from("");
+        Ok(())
+    }
+    ///The capabilities negotiated with the server when the session was created
+    pub fn capabilities(&self) -> &SessionCapabilities {
+        &self.capabilities
+    }
+    ///Sets the observer notified about every request sent by this AsyncBrowser and its Elements,
+    /// e.g. the FileWireLogger. It replaces the previously set one.
+    pub fn set_wire_observer<O: WireObserver + 'static>(&mut self, observer: O) -> &mut Self {
+        self.transport.set_observer(Arc::new(observer));
+        self
+    }
+    ///Keeps the session open on the server when the AsyncBrowser is dropped
+    pub fn detach(&mut self) {
+        self.detached = true;
+    }
+    pub async fn get_timeouts(&self) -> Result<Timeouts, WebDriverError> {
+        let resp = self.command(Method::GET, "timeouts", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn set_timeouts(&self, timeouts: &Timeouts) -> Result<(), WebDriverError> {
+        let body = serde_json::to_string(timeouts)?;
+        self.command(Method::POST, "timeouts", &body).await?;
+        Ok(())
+    }
+    pub async fn back(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "back", r#"{"return":true}"#)
+            .await?;
+        Ok(())
+    }
+    pub async fn forward(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "forward", r#"{"forward":true}"#)
+            .await?;
+        Ok(())
+    }
+    pub async fn refresh(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "refresh", r#"{"refresh":true}"#)
+            .await?;
+        Ok(())
+    }
+    pub async fn get_title(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "title", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_window_handle(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "window", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_window_handles(&self) -> Result<Vec<String>, WebDriverError> {
+        let resp = self.command(Method::GET, "window/handles", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn switch_to_window(&self, window_id: String) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "handle": window_id }).to_string();
+        self.command(Method::POST, "window", &body).await?;
+        Ok(())
+    }
+    ///Opens a new window or a new tab and returns its handle and type
+    pub async fn new_window(
+        &self,
+        window_type: NewWindowType,
+    ) -> Result<(String, String), WebDriverError> {
+        let body = match window_type {
+            NewWindowType::Tab => r#"{"type":"tab"}"#,
+            NewWindowType::Window => r#"{"type":"window"}"#,
+        };
+        let resp = self.command(Method::POST, "window/new", body).await?;
+        let map: HashMap<String, String> = serde_json::from_value(resp)?;
+        match (map.get("handle"), map.get("type")) {
+            (Some(handle), Some(wtype)) => Ok((handle.clone(), wtype.clone())),
+            _ => Err(WebDriverError::InvalidResponse(String::from(
+                "The new window handle or type is missing",
+            ))),
+        }
+    }
+    ///Closes the window and returns the vector of the remaining window handles
+    pub async fn close_window(&self) -> Result<Vec<String>, WebDriverError> {
+        let resp = self.command(Method::DELETE, "window", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn switch_to_frame_by_id(&self, id: u64) -> Result<(), WebDriverError> {
+        let body = format!(r#"{{"id":{}}}"#, id);
+        self.command(Method::POST, "frame", &body).await?;
+        Ok(())
+    }
+    pub async fn switch_to_frame_by_element(
+        &self,
+        element: AsyncElement,
+    ) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({
+            "id": { element.element_gr_id.as_str(): element.element_id.as_str() }
+        })
+        .to_string();
+        self.command(Method::POST, "frame", &body).await?;
+        Ok(())
+    }
+    pub async fn switch_to_parent_frame(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "frame/parent", "{}").await?;
+        Ok(())
+    }
+    pub async fn get_active_element(&self) -> Result<AsyncElement, WebDriverError> {
+        let resp = self.command(Method::GET, "element/active", "").await?;
+        self.element(resp)
+    }
+    ///If the locator matches several elements, it returns the first one
+    pub async fn find_element<S: AsRef<str>>(
+        &self,
+        loc_strategy: LocatorStrategy<S>,
+    ) -> Result<AsyncElement, WebDriverError> {
+        let body = body_for_find_element(&loc_strategy);
+        let resp = self.command(Method::POST, "element", &body).await?;
+        self.element(resp)
+    }
+    pub async fn find_elements<S: AsRef<str>>(
+        &self,
+        loc_strategy: LocatorStrategy<S>,
+    ) -> Result<Vec<AsyncElement>, WebDriverError> {
+        let body = body_for_find_element(&loc_strategy);
+        let resp = self.command(Method::POST, "elements", &body).await?;
+        let refs: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        refs.into_iter().map(|r| self.element(r)).collect()
+    }
+    pub async fn get_window_rect(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = self.command(Method::GET, "window/rect", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn set_window_rect(
+        &self,
+        window_rect: &WindowRect,
+    ) -> Result<WindowRect, WebDriverError> {
+        let body = serde_json::to_string(window_rect)?;
+        let resp = self.command(Method::POST, "window/rect", &body).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn maximize_window(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = self.command(Method::POST, "window/maximize", "{}").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn minimize_window(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = self.command(Method::POST, "window/minimize", "{}").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn fullscreen(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = self
+            .command(Method::POST, "window/fullscreen", "{}")
+            .await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Returns the page source code
+    pub async fn source(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "source", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_all_cookies(&self) -> Result<Vec<Cookie>, WebDriverError> {
+        let resp = self.command(Method::GET, "cookie", "").await?;
+        let cookies: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        cookies.iter().map(from_value_to_cookie).collect()
+    }
+    pub async fn get_cookie(&self, cookie_name: &str) -> Result<Cookie, WebDriverError> {
+        let endpoint = format!("cookie/{}", cookie_name);
+        let resp = self.command(Method::GET, &endpoint, "").await?;
+        from_value_to_cookie(&resp)
+    }
+    pub async fn add_cookie(&self, cookie: Cookie) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "cookie": cookie }).to_string();
+        self.command(Method::POST, "cookie", &body).await?;
+        Ok(())
+    }
+    pub async fn delete_cookie(&self, cookie_name: &str) -> Result<(), WebDriverError> {
+        let endpoint = format!("cookie/{}", cookie_name);
+        self.command(Method::DELETE, &endpoint, "").await?;
+        Ok(())
+    }
+    pub async fn delete_all_cookies(&self) -> Result<(), WebDriverError> {
+        self.command(Method::DELETE, "cookie", "").await?;
+        Ok(())
+    }
+    ///The path should be absolute with the extension
+    pub async fn take_screenshot(&self, path: &str) -> Result<(), WebDriverError> {
+        let image = self.get_screenshot_png().await?;
+        tokio::fs::write(path, image)
+            .await
+            .map_err(WebDriverError::Io)
+    }
+    pub async fn take_element_screenshot(
+        &self,
+        elem: &AsyncElement,
+        path: &str,
+    ) -> Result<(), WebDriverError> {
+        let image = self.get_element_screenshot_png(elem).await?;
+        tokio::fs::write(path, image)
+            .await
+            .map_err(WebDriverError::Io)
+    }
+    ///See Browser::get_screenshot_png
+    pub async fn get_screenshot_png(&self) -> Result<Vec<u8>, WebDriverError> {
+        let resp = self.command(Method::GET, "screenshot", "").await?;
+        decode_base64_value(resp)
+    }
+    pub async fn get_element_screenshot_png(
+        &self,
+        elem: &AsyncElement,
+    ) -> Result<Vec<u8>, WebDriverError> {
+        let endpoint = format!("element/{}/screenshot", elem.element_id);
+        let resp = self.command(Method::GET, &endpoint, "").await?;
+        decode_base64_value(resp)
+    }
+    ///See Browser::execute_sync for the format of the args
+    pub async fn execute_sync(
+        &self,
+        script: &str,
+        args: &Vec<&str>,
+    ) -> Result<String, WebDriverError> {
+        let args = gen_script_args(args);
+        let body = format!(r#"{{"script":"{}","args":{}}}"#, script, args);
+        let resp = self.command(Method::POST, "execute/sync", &body).await?;
+        Ok(resp.to_string())
+    }
+    pub async fn execute_async(
+        &self,
+        script: &str,
+        args: &Vec<&str>,
+    ) -> Result<String, WebDriverError> {
+        let args = gen_script_args(args);
+        let body = format!(r#"{{"script":"{}","args":{}}}"#, script, args);
+        let resp = self.command(Method::POST, "execute/async", &body).await?;
+        Ok(resp.to_string())
+    }
+    ///See Browser::execute
+    pub async fn execute<T: DeserializeOwned, A: Serialize>(
+        &self,
+        script: &str,
+        args: A,
+    ) -> Result<T, WebDriverError> {
+        let body = script_body(script, args)?;
+        let resp = self.command(Method::POST, "execute/sync", &body).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///See Browser::execute_async_as
+    pub async fn execute_async_as<T: DeserializeOwned, A: Serialize>(
+        &self,
+        script: &str,
+        args: A,
+    ) -> Result<T, WebDriverError> {
+        let body = script_body(script, args)?;
+        let resp = self.command(Method::POST, "execute/async", &body).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn print(
+        &self,
+        print_settings: &PrintSettings,
+        path: &str,
+    ) -> Result<(), WebDriverError> {
+        let body = serde_json::to_string(&print_settings)?;
+        let resp = self.command(Method::POST, "print", &body).await?;
+        let pdf = decode_base64_value(resp)?;
+        tokio::fs::write(path, pdf)
+            .await
+            .map_err(WebDriverError::Io)
+    }
+    pub async fn dismiss_alert(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "alert/dismiss", "{}").await?;
+        Ok(())
+    }
+    pub async fn allow_alert(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "alert/accept", "{}").await?;
+        Ok(())
+    }
+    pub async fn get_alert_text(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "alert/text", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn send_alert_text(&self, text: &str) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "text": text }).to_string();
+        self.command(Method::POST, "alert/text", &body).await?;
+        Ok(())
+    }
+    ///See Browser::perform_actions
+    pub async fn perform_actions(&self, actions: Actions) -> Result<(), WebDriverError> {
+        let mut actions = actions;
+        actions.set_ids();
+        let body = serde_json::to_string(&actions)?;
+        self.command(Method::POST, "actions", &body).await?;
+        Ok(())
+    }
+    pub async fn release_actions(&self) -> Result<(), WebDriverError> {
+        self.command(Method::DELETE, "actions", "").await?;
+        Ok(())
+    }
+}
+impl Drop for AsyncBrowser {
+    fn drop(&mut self) {
+        if self.detached || self.transport.is_closed() {
+            return;
+        }
+        self.transport.close();
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let transport = self.transport.shared_inner();
+            let session_url = std::mem::take(&mut self.session_url);
+            runtime.spawn(async move {
+                let _ = send_command_async(&*transport, Method::DELETE, &session_url, "").await;
+            });
+        }
+    }
+}
+async fn async_session_at(
+    url: &ServerUrl,
+    body: &str,
+    observer: Option<Arc<dyn WireObserver>>,
+) -> Result<AsyncBrowser, WebDriverError> {
+    let transport = AsyncTcpTransport::new(url.get_host(), &url.get_port().to_string());
+    new_async_session(Arc::new(transport), url, body, observer).await
+}
+async fn new_async_session(
+    transport: Arc<dyn AsyncTransport>,
+    url: &ServerUrl,
+    body: &str,
+    observer: Option<Arc<dyn WireObserver>>,
+) -> Result<AsyncBrowser, WebDriverError> {
+    let transport = Arc::new(SessionTransport::new(transport));
+    if let Some(observer) = observer {
+        transport.set_observer(observer);
+    }
+    let resp =
+        send_command_async(&*transport, Method::POST, &url.endpoint("session"), body).await?;
+    let session: Session = serde_json::from_value(resp)?;
+    Ok(AsyncBrowser {
+        transport,
+        session_url: url.endpoint(&format!("session/{}", session.sessionId)),
+        capabilities: session.capabilities,
+        detached: false,
+    })
+}
+
+mod async_browser_tests {
+    use super::*;
+    use crate::transport::*;
+    #[tokio::test]
+    async fn find_and_read_text() {
+        let (mock, br) = mock_async_browser("chrome").await;
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/e1/text",
+            r#"{"value":"Hello"}"#,
+        );
+        let el = br.find_element(LocatorStrategy::CSS("#a")).await.unwrap();
+        assert_eq!(el.get_element_text().await.unwrap(), "Hello");
+        assert!(mock.is_done());
+    }
+    #[tokio::test]
+    async fn child_elements_keep_the_url_prefix() {
+        let mock = MockTransport::new();
+        mock.expect(
+            Method::POST,
+            "grid/element-hub/session",
+            r#"{"value":{"sessionId":"s1","capabilities":{}}}"#,
+        )
+        .expect(
+            Method::POST,
+            "grid/element-hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "grid/element-hub/session/s1/element/e1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e2"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "grid/element-hub/session/s1/element/e2/text",
+            r#"{"value":"Child"}"#,
+        );
+        let url = ServerUrl::parse("http://localhost:4444/grid/element-hub").unwrap();
+        let c = Capabilities::new(BrowserName::Chrome, "linux");
+        let mut br = new_async_session(Arc::new(mock.clone()), &url, &c.cap_string, None)
+            .await
+            .unwrap();
+        br.detach();
+        let parent = br.find_element(LocatorStrategy::CSS("ul")).await.unwrap();
+        let child = parent
+            .find_element_from_self(LocatorStrategy::CSS("li"))
+            .await
+            .unwrap();
+        assert_eq!(child.get_element_text().await.unwrap(), "Child");
+        assert!(mock.is_done());
+    }
+    #[tokio::test]
+    async fn cookies_and_errors() {
+        let (mock, br) = mock_async_browser("chrome").await;
+        mock.expect(
+            Method::GET,
+            "wd/hub/session/s1/cookie",
+            r#"{"value":[{"name":"a","value":"1"}]}"#,
+        )
+        .expect_status(
+            Method::GET,
+            "wd/hub/session/s1/alert/text",
+            404,
+            r#"{"value":{"error":"no such alert","message":"none","stacktrace":""}}"#,
+        );
+        let cookies = br.get_all_cookies().await.unwrap();
+        assert_eq!(cookies[0].get_value(), "1");
+        let err = br.get_alert_text().await.unwrap_err();
+        assert!(matches!(err, WebDriverError::NoSuchAlert(_)));
+    }
+    #[tokio::test]
+    async fn drop_deletes_session() {
+        let (mock, br) = mock_async_browser("chrome").await;
+        mock.expect(Method::DELETE, "wd/hub/session/s1", r#"{"value":null}"#);
+        drop(br);
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(mock.is_done());
+    }
+    #[tokio::test]
+    async fn closed_session_sends_nothing() {
+        let (mock, mut br) = mock_async_browser("chrome").await;
+        mock.expect(Method::DELETE, "wd/hub/session/s1", r#"{"value":null}"#);
+        br.close_browser().await.unwrap();
+        let err = br.get_title().await.unwrap_err();
+        assert!(matches!(err, WebDriverError::SessionClosed));
+        drop(br);
+        tokio::task::yield_now().await;
+        assert_eq!(mock.requests().len(), 2);
+    }
+    #[tokio::test]
+    async fn sessions_run_concurrently() {
+        let (first_mock, first) = mock_async_browser("chrome").await;
+        let (second_mock, second) = mock_async_browser("chrome").await;
+        first_mock.expect(Method::GET, "wd/hub/session/s1/title", r#"{"value":"A"}"#);
+        second_mock.expect(Method::GET, "wd/hub/session/s1/title", r#"{"value":"B"}"#);
+        let (a, b) = tokio::join!(first.get_title(), second.get_title());
+        assert_eq!(
+            (a.unwrap(), b.unwrap()),
+            (String::from("A"), String::from("B"))
+        );
+    }
+}
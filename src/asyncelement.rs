@@ -0,0 +1,130 @@
+use super::asynctransport::*;
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+use std::sync::Arc;
+
+///The async counterpart of the Element, returned by the AsyncBrowser
+///
+/// Has the same methods as the Element, which have to be awaited.
+#[derive(Debug)]
+pub struct AsyncElement {
+    pub(crate) transport: Arc<dyn AsyncTransport>,
+    pub(crate) element_gr_id: String,
+    pub(crate) element_id: String,
+    pub(crate) element_url: String,
+    session_url: String,
+}
+impl AsyncElement {
+    pub(crate) fn from_reference(
+        transport: &Arc<dyn AsyncTransport>,
+        session_url: &str,
+        reference: serde_json::Value,
+    ) -> Result<AsyncElement, WebDriverError> {
+        let (gr_id, id) = element_reference(reference)?;
+        Ok(AsyncElement {
+            transport: transport.clone(),
+            element_url: format!("{}/element/{}", session_url, id),
+            element_gr_id: gr_id,
+            element_id: id,
+            session_url: String::from(session_url),
+        })
+    }
+    async fn command(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: &str,
+    ) -> Result<serde_json::Value, WebDriverError> {
+        let url = format!("{}/{}", self.element_url, endpoint);
+        send_command_async(&*self.transport, method, &url, body).await
+    }
+    fn session_url(&self) -> &str {
+        &self.session_url
+    }
+    ///Returns the first child element which is found using the locator.
+    pub async fn find_element_from_self<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<AsyncElement, WebDriverError> {
+        let body = body_for_find_element(&locator);
+        let resp = self.command(Method::POST, "element", &body).await?;
+        AsyncElement::from_reference(&self.transport, self.session_url(), resp)
+    }
+    ///Returns all children elements which are found using the locator.
+    pub async fn find_elements_from_self<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<AsyncElement>, WebDriverError> {
+        let body = body_for_find_element(&locator);
+        let resp = self.command(Method::POST, "elements", &body).await?;
+        let refs: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        refs.into_iter()
+            .map(|r| AsyncElement::from_reference(&self.transport, self.session_url(), r))
+            .collect()
+    }
+    pub async fn is_selected(&self) -> Result<bool, WebDriverError> {
+        let resp = self.command(Method::GET, "selected", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Returns "null" if the element does not have the attribute
+    pub async fn get_attribute(&self, attribute_name: &str) -> Result<String, WebDriverError> {
+        let endpoint = format!("attribute/{}", attribute_name);
+        let resp = self.command(Method::GET, &endpoint, "").await?;
+        if resp.is_null() {
+            return Ok(String::from("null"));
+        }
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///The json of the property value is returned, "null" if there is no such property
+    pub async fn get_property(&self, property_name: &str) -> Result<String, WebDriverError> {
+        let endpoint = format!("property/{}", property_name);
+        let resp = self.command(Method::GET, &endpoint, "").await?;
+        Ok(resp.to_string())
+    }
+    pub async fn get_css_value(&self, css_property_name: &str) -> Result<String, WebDriverError> {
+        let endpoint = format!("css/{}", css_property_name);
+        let resp = self.command(Method::GET, &endpoint, "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_element_text(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "text", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_tag_name(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "name", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Returns the element's size(hight,width) and position(x-axis and y-axis)
+    pub async fn get_element_rect(&self) -> Result<ElementRect, WebDriverError> {
+        let resp = self.command(Method::GET, "rect", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn is_enabled(&self) -> Result<bool, WebDriverError> {
+        let resp = self.command(Method::GET, "enabled", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_computed_role(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "computedrole", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn get_computed_label(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "computedlabel", "").await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub async fn click(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "click", "{}").await?;
+        Ok(())
+    }
+    ///Clears any element text
+    pub async fn clear_element(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "clear", "{}").await?;
+        Ok(())
+    }
+    ///Sends the text to the element if it is possibe for the element, otherwise, returns error
+    pub async fn send_keys(&self, message: &str) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "text": message }).to_string();
+        self.command(Method::POST, "value", &body).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+use super::error::*;
+use super::reqs::*;
+use super::transport::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+///The non-blocking counterpart of the Transport trait used by the AsyncBrowser and AsyncElement
+///
+/// The MockTransport implements both traits, so the same scripted responses may be used
+/// to test the async code.
+pub trait AsyncTransport: Send + Sync + std::fmt::Debug {
+    fn send<'a>(
+        &'a self,
+        method: Method,
+        path: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<HttpResponse, WebDriverError>>;
+}
+
+///Sends the requests over tokio keep-alive connections, pooled the same way as in TcpTransport
+#[derive(Debug)]
+pub struct AsyncTcpTransport {
+    ip: String,
+    port: String,
+    max_idle_connections: usize,
+    pool: Mutex<Vec<BufReader<TcpStream>>>,
+}
+impl AsyncTcpTransport {
+    pub fn new(ip: &str, port: &str) -> Self {
+        AsyncTcpTransport {
+            ip: String::from(ip),
+            port: String::from(port),
+            max_idle_connections: 4,
+            pool: Mutex::new(vec![]),
+        }
+    }
+    ///Zero disables the pooling, so every request opens a new connection
+    pub fn set_max_idle_connections(&mut self, max_idle_connections: usize) -> &mut Self {
+        self.max_idle_connections = max_idle_connections;
+        self
+    }
+    async fn connect(&self) -> Result<BufReader<TcpStream>, WebDriverError> {
+        let address = format!("{}:{}", self.ip, self.port);
+        let connection = TcpStream::connect(address).await?;
+        connection.set_nodelay(true)?;
+        Ok(BufReader::new(connection))
+    }
+    //Skips the idle connections the server has closed meanwhile
+    fn take_idle(&self) -> Option<BufReader<TcpStream>> {
+        let mut pool = self.pool.lock().unwrap();
+        while let Some(connection) = pool.pop() {
+            if is_open(&connection) {
+                return Some(connection);
+            }
+        }
+        None
+    }
+    fn release(&self, connection: BufReader<TcpStream>) {
+        let mut pool = self.pool.lock().unwrap();
+        if pool.len() < self.max_idle_connections {
+            pool.push(connection);
+        }
+    }
+    async fn send_request(
+        &self,
+        method: Method,
+        path: &str,
+        body: &str,
+    ) -> Result<HttpResponse, WebDriverError> {
+        let headers = match method {
+            Method::POST => cont_length_header(body),
+            _ => vec![],
+        };
+        let host = format!("{}:{}", self.ip, self.port);
+        let request = create_req(method, &host, path, headers, body);
+        loop {
+            let pooled = self.take_idle();
+            let reused = pooled.is_some();
+            let mut connection = match pooled {
+                Some(connection) => connection,
+                None => self.connect().await?,
+            };
+            //The same rules as in TcpTransport: the request which could not be written is sent again,
+            //the POST which was written is not, since the server may have executed it
+            match write_request(&mut connection, &request).await {
+                Err(WebDriverError::Transport(_)) if reused => continue,
+                Err(e) => return Err(e),
+                Ok(()) => (),
+            }
+            match read_response_async(&mut connection).await {
+                Ok(response) => {
+                    if reusable(&response) {
+                        self.release(connection);
+                    }
+                    return Ok(response);
+                }
+                Err(WebDriverError::Transport(_)) if reused && method != Method::POST => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+impl AsyncTransport for AsyncTcpTransport {
+    fn send<'a>(
+        &'a self,
+        method: Method,
+        path: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<HttpResponse, WebDriverError>> {
+        Box::pin(self.send_request(method, path, body))
+    }
+}
+impl AsyncTransport for SessionTransport<dyn AsyncTransport> {
+    fn send<'a>(
+        &'a self,
+        method: Method,
+        path: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<HttpResponse, WebDriverError>> {
+        Box::pin(async move {
+            let inner = self.inner()?;
+            let observer = match self.observer() {
+                Some(observer) => observer,
+                None => return inner.send(method, path, body).await,
+            };
+            observer.on_request(method, path, body);
+            let started = Instant::now();
+            let result = inner.send(method, path, body).await;
+            observer.on_response(method, path, &result, started.elapsed());
+            result
+        })
+    }
+}
+impl AsyncTransport for MockTransport {
+    fn send<'a>(
+        &'a self,
+        method: Method,
+        path: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<HttpResponse, WebDriverError>> {
+        Box::pin(async move { Transport::send(self, method, path, body) })
+    }
+}
+async fn write_request(
+    connection: &mut BufReader<TcpStream>,
+    request: &str,
+) -> Result<(), WebDriverError> {
+    let stream = connection.get_mut();
+    stream.write_all(request.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+//The connection closed by the server reads as the end of the stream without waiting
+fn is_open(connection: &BufReader<TcpStream>) -> bool {
+    let mut byte = [0; 1];
+    connection.buffer().is_empty()
+        && matches!(connection.get_ref().try_read(&mut byte), Err(e) if e.kind() == std::io::ErrorKind::WouldBlock)
+}
+///Reads one HTTP/1.1 response, see read_response for the details
+pub(crate) async fn read_response_async<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> Result<HttpResponse, WebDriverError> {
+    loop {
+        let mut line = read_line(reader).await?;
+        while line.is_empty() {
+            line = read_line(reader).await?;
+        }
+        let status = parse_status_line(&line)?;
+        let headers = read_headers(reader).await?;
+        let mut response = HttpResponse::new(status, headers, vec![]);
+        match body_length(&response)? {
+            BodyLength::Interim => continue,
+            BodyLength::Empty => (),
+            BodyLength::Chunked => response.body = read_chunked_body(reader).await?,
+            BodyLength::Length(length) => {
+                response.body = vec![0; length];
+                reader.read_exact(&mut response.body).await?;
+            }
+            BodyLength::UntilClose => {
+                reader.read_to_end(&mut response.body).await?;
+            }
+        }
+        return Ok(response);
+    }
+}
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<String, WebDriverError> {
+    let mut line = vec![];
+    let read = reader.read_until(b'\n', &mut line).await?;
+    line_from_bytes(line, read)
+}
+async fn read_headers<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> Result<Vec<(String, String)>, WebDriverError> {
+    let mut headers = vec![];
+    loop {
+        let line = read_line(reader).await?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        headers.push(parse_header_line(&line)?);
+    }
+}
+async fn read_chunked_body<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> Result<Vec<u8>, WebDriverError> {
+    let mut body = vec![];
+    loop {
+        let size = parse_chunk_size(&read_line(reader).await?)?;
+        if size == 0 {
+            read_headers(reader).await?;
+            return Ok(body);
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..]).await?;
+        read_line(reader).await?;
+    }
+}
+pub(crate) async fn send_command_async(
+    transport: &dyn AsyncTransport,
+    method: Method,
+    path: &str,
+    body: &str,
+) -> Result<serde_json::Value, WebDriverError> {
+    let resp = transport.send(method, path, body).await?;
+    parse_response(resp.status, &resp.body)
+}
+
+mod async_transport_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    #[tokio::test]
+    async fn chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"va\r\nA\r\nlue\":true}\r\n0\r\n\r\n";
+        let mut reader = &raw[..];
+        let resp = read_response_async(&mut reader).await.unwrap();
+        assert_eq!(resp.get_body(), br#"{"value":true}"#);
+    }
+    //Answers every request with its number on the connection,
+    //but drops the connection instead of answering the request number drop_at
+    async fn serve(drop_at: usize) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port().to_string();
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut n = 0;
+                while let Some(body) = read_request(&mut reader).await {
+                    n += 1;
+                    if counter.fetch_add(1, Ordering::SeqCst) + 1 == drop_at {
+                        break;
+                    }
+                    let body = if body.is_empty() {
+                        format!(r#"{{"value":{}}}"#, n)
+                    } else {
+                        body
+                    };
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    reader.get_mut().write_all(resp.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        (port, received)
+    }
+    async fn read_request(reader: &mut BufReader<TcpStream>) -> Option<String> {
+        let mut line = String::new();
+        let mut length = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await.unwrap() == 0 {
+                return None;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(l) = line.strip_prefix("Content-Length: ") {
+                length = l.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await.unwrap();
+        Some(String::from_utf8(body).unwrap())
+    }
+    #[tokio::test]
+    async fn keep_alive() {
+        let (port, _) = serve(0).await;
+        let transport = AsyncTcpTransport::new("127.0.0.1", &port);
+        for i in 0..3 {
+            let body = format!(r#"{{"value":{}}}"#, i);
+            let resp = transport.send(Method::POST, "echo", &body).await.unwrap();
+            assert_eq!(resp.get_body(), body.as_bytes());
+        }
+    }
+    #[tokio::test]
+    async fn post_is_not_repeated_after_it_was_sent() {
+        let (port, received) = serve(2).await;
+        let transport = AsyncTcpTransport::new("127.0.0.1", &port);
+        transport.send(Method::POST, "x", "").await.unwrap();
+        let res = transport.send(Method::POST, "element/e1/click", "").await;
+        assert!(matches!(res, Err(WebDriverError::Transport(_))));
+        assert_eq!(received.load(Ordering::SeqCst), 2);
+        let resp = transport.send(Method::GET, "x", "").await.unwrap();
+        assert_eq!(resp.get_body(), br#"{"value":1}"#);
+    }
+    #[tokio::test]
+    async fn get_is_repeated_on_broken_connection() {
+        let (port, received) = serve(2).await;
+        let transport = AsyncTcpTransport::new("127.0.0.1", &port);
+        transport.send(Method::GET, "x", "").await.unwrap();
+        let resp = transport.send(Method::GET, "x", "").await.unwrap();
+        assert_eq!(resp.get_body(), br#"{"value":1}"#);
+        assert_eq!(received.load(Ordering::SeqCst), 3);
+    }
+}
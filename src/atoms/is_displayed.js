@@ -0,0 +1,321 @@
+// An approximation of the Selenium isDisplayed atom, not the upstream code: a hand-written
+// function following the approach of bot.dom.isShown (javascript/atoms/dom.js) without the
+// branches for the browsers older than the W3C drivers (old IE styles and the v0 shadow DOM).
+// Its results may differ from the upstream atom in the edge cases.
+// Called with the element as the first argument, returns true if the user can see it.
+function (elem) {
+  var OVERFLOW_NONE = 'none';
+  var OVERFLOW_SCROLL = 'scroll';
+  var OVERFLOW_HIDDEN = 'hidden';
+
+  function isElement(node, tagName) {
+    return !!node && node.nodeType == 1 &&
+      (!tagName || node.tagName.toUpperCase() == tagName);
+  }
+
+  function getEffectiveStyle(e, property) {
+    var style = window.getComputedStyle(e, null);
+    return style ? style.getPropertyValue(property) || style[property] || null : null;
+  }
+
+  function getParentElement(node) {
+    var parent = node.parentNode;
+    while (parent && parent.nodeType != 1 && parent.nodeType != 9 && parent.nodeType != 11) {
+      parent = parent.parentNode;
+    }
+    return isElement(parent) ? parent : null;
+  }
+
+  function getAncestor(node, matches) {
+    for (var e = getParentElement(node); e; e = getParentElement(e)) {
+      if (matches(e)) {
+        return e;
+      }
+    }
+    return null;
+  }
+
+  function getParentNodeInComposedDom(node) {
+    var parent = node.parentNode;
+    if (parent && parent.shadowRoot && node.assignedSlot !== undefined) {
+      return node.assignedSlot ? node.assignedSlot.parentNode : null;
+    }
+    return parent;
+  }
+
+  function rect(left, top, width, height) {
+    return { left: left, top: top, width: width, height: height, right: left + width, bottom: top + height };
+  }
+
+  function getClientRect(e) {
+    var r = e.getBoundingClientRect();
+    return rect(r.left, r.top, r.right - r.left, r.bottom - r.top);
+  }
+
+  function getAreaRelativeRect(area) {
+    var shape = area.shape.toLowerCase();
+    var coords = area.coords.split(',');
+    if (shape == 'rect' && coords.length == 4) {
+      var x = Number(coords[0]), y = Number(coords[1]);
+      return rect(x, y, Number(coords[2]) - x, Number(coords[3]) - y);
+    } else if (shape == 'circle' && coords.length == 3) {
+      var centerX = Number(coords[0]), centerY = Number(coords[1]), radius = Number(coords[2]);
+      return rect(centerX - radius, centerY - radius, 2 * radius, 2 * radius);
+    } else if (shape == 'poly' && coords.length > 2) {
+      var minX = Number(coords[0]), minY = Number(coords[1]), maxX = minX, maxY = minY;
+      for (var i = 2; i + 1 < coords.length; i += 2) {
+        minX = Math.min(minX, Number(coords[i]));
+        maxX = Math.max(maxX, Number(coords[i]));
+        minY = Math.min(minY, Number(coords[i + 1]));
+        maxY = Math.max(maxY, Number(coords[i + 1]));
+      }
+      return rect(minX, minY, maxX - minX, maxY - minY);
+    }
+    return rect(0, 0, 0, 0);
+  }
+
+  // Returns the image using the map the element (a MAP or an AREA) belongs to, with the element's rect
+  function maybeFindImageMap(e) {
+    var isMap = isElement(e, 'MAP');
+    if (!isMap && !isElement(e, 'AREA')) {
+      return null;
+    }
+    var map = isMap ? e : getAncestor(e, function (a) { return isElement(a, 'MAP'); });
+    var image = null, r = null;
+    if (map && map.name) {
+      image = map.ownerDocument.querySelector('*[usemap="#' + map.name + '"]');
+      if (image) {
+        r = getClientRect(image);
+        if (!isMap && e.shape.toLowerCase() != 'default') {
+          var relRect = getAreaRelativeRect(e);
+          var relX = Math.min(Math.max(relRect.left, 0), r.width);
+          var relY = Math.min(Math.max(relRect.top, 0), r.height);
+          var w = Math.min(relRect.width, r.width - relX);
+          var h = Math.min(relRect.height, r.height - relY);
+          r = rect(relX + r.left, relY + r.top, w, h);
+        }
+      }
+    }
+    return { image: image, rect: r || rect(0, 0, 0, 0) };
+  }
+
+  function getOpacity(e) {
+    var opacity = 1;
+    var opacityStyle = getEffectiveStyle(e, 'opacity');
+    if (opacityStyle) {
+      opacity = Number(opacityStyle);
+    }
+    var parent = getParentElement(e);
+    if (parent) {
+      opacity = opacity * getOpacity(parent);
+    }
+    return opacity;
+  }
+
+  function getOverflowState(e, region) {
+    region = region || getClientRect(e);
+    var ownerDoc = e.ownerDocument;
+    var htmlElem = ownerDoc.documentElement;
+    var bodyElem = ownerDoc.body;
+    var htmlOverflowStyle = getEffectiveStyle(htmlElem, 'overflow');
+    var treatAsFixedPosition;
+
+    // The closest ancestor the element may overflow
+    function getOverflowParent(child) {
+      var position = getEffectiveStyle(child, 'position');
+      if (position == 'fixed') {
+        treatAsFixedPosition = true;
+        // A fixed element may only overflow the viewport
+        return child == htmlElem ? null : htmlElem;
+      }
+      var parent = getParentElement(child);
+      while (parent && !canBeOverflowed(parent)) {
+        parent = getParentElement(parent);
+      }
+      return parent;
+
+      function canBeOverflowed(container) {
+        if (container == htmlElem) {
+          return true;
+        }
+        var containerDisplay = getEffectiveStyle(container, 'display');
+        if (containerDisplay.indexOf('inline') == 0 || containerDisplay == 'contents') {
+          return false;
+        }
+        if (position == 'absolute' && getEffectiveStyle(container, 'position') == 'static') {
+          return false;
+        }
+        return true;
+      }
+    }
+
+    function getOverflowStyles(container) {
+      // The html element with the visible overflow takes the overflow of the body,
+      // and the body's one is really visible then
+      var overflowElem = container;
+      if (htmlOverflowStyle == 'visible') {
+        if (container == htmlElem && bodyElem) {
+          overflowElem = bodyElem;
+        } else if (container == bodyElem) {
+          return { x: 'visible', y: 'visible' };
+        }
+      }
+      var overflow = {
+        x: getEffectiveStyle(overflowElem, 'overflow-x'),
+        y: getEffectiveStyle(overflowElem, 'overflow-y')
+      };
+      // The viewport can not expand, so the visible overflow of the html element is really auto
+      if (container == htmlElem) {
+        overflow.x = overflow.x == 'visible' ? 'auto' : overflow.x;
+        overflow.y = overflow.y == 'visible' ? 'auto' : overflow.y;
+      }
+      return overflow;
+    }
+
+    function getScroll(container) {
+      if (container == htmlElem) {
+        var win = ownerDoc.defaultView;
+        return { x: win.pageXOffset || htmlElem.scrollLeft, y: win.pageYOffset || htmlElem.scrollTop };
+      }
+      return { x: container.scrollLeft, y: container.scrollTop };
+    }
+
+    for (var container = getOverflowParent(e); !!container; container = getOverflowParent(container)) {
+      var containerOverflow = getOverflowStyles(container);
+      if (containerOverflow.x == 'visible' && containerOverflow.y == 'visible') {
+        continue;
+      }
+      var containerRect = getClientRect(container);
+      // A zero-sized container without the visible overflow hides all its descendants
+      if (containerRect.width == 0 || containerRect.height == 0) {
+        return OVERFLOW_HIDDEN;
+      }
+      // The element is to the left or above the container
+      var underflowsX = region.right < containerRect.left;
+      var underflowsY = region.bottom < containerRect.top;
+      if ((underflowsX && containerOverflow.x == 'hidden') ||
+        (underflowsY && containerOverflow.y == 'hidden')) {
+        return OVERFLOW_HIDDEN;
+      } else if ((underflowsX && containerOverflow.x != 'visible') ||
+        (underflowsY && containerOverflow.y != 'visible')) {
+        var containerScroll = getScroll(container);
+        var unscrollableX = region.right < containerRect.left - containerScroll.x;
+        var unscrollableY = region.bottom < containerRect.top - containerScroll.y;
+        if ((unscrollableX && containerOverflow.x != 'visible') ||
+          (unscrollableY && containerOverflow.x != 'visible')) {
+          return OVERFLOW_HIDDEN;
+        }
+        return getOverflowState(container) == OVERFLOW_HIDDEN ? OVERFLOW_HIDDEN : OVERFLOW_SCROLL;
+      }
+      // The element is to the right or below the container
+      var overflowsX = region.left >= containerRect.left + containerRect.width;
+      var overflowsY = region.top >= containerRect.top + containerRect.height;
+      if ((overflowsX && containerOverflow.x == 'hidden') ||
+        (overflowsY && containerOverflow.y == 'hidden')) {
+        return OVERFLOW_HIDDEN;
+      } else if ((overflowsX && containerOverflow.x != 'visible') ||
+        (overflowsY && containerOverflow.y != 'visible')) {
+        // A fixed element outside the scrollable area of the document is hidden
+        if (treatAsFixedPosition) {
+          var docScroll = getScroll(container);
+          if ((region.left >= htmlElem.scrollWidth - docScroll.x) ||
+            (region.right >= htmlElem.scrollHeight - docScroll.y)) {
+            return OVERFLOW_HIDDEN;
+          }
+        }
+        return getOverflowState(container) == OVERFLOW_HIDDEN ? OVERFLOW_HIDDEN : OVERFLOW_SCROLL;
+      }
+    }
+    return OVERFLOW_NONE;
+  }
+
+  // Checks the display style of the element and its ancestors in the composed tree
+  function displayed(e) {
+    if (isElement(e) && getEffectiveStyle(e, 'display') == 'none') {
+      return false;
+    }
+    var parent = getParentNodeInComposedDom(e);
+    if (typeof ShadowRoot == 'function' && parent instanceof ShadowRoot) {
+      if (parent.host.shadowRoot && parent.host.shadowRoot !== parent) {
+        return false;
+      }
+      parent = parent.host;
+    }
+    if (parent && (parent.nodeType == 9 || parent.nodeType == 11)) {
+      return true;
+    }
+    // A child of a closed DETAILS element is not shown, unless it is the SUMMARY
+    if (parent && isElement(parent, 'DETAILS') && !parent.open && !isElement(e, 'SUMMARY')) {
+      return false;
+    }
+    return !!parent && displayed(parent);
+  }
+
+  function isShown(e, ignoreOpacity) {
+    if (!isElement(e)) {
+      throw new Error('Argument to isShown must be of type Element');
+    }
+    // By convention, the BODY element is always shown
+    if (isElement(e, 'BODY')) {
+      return true;
+    }
+    // An OPTION or OPTGROUP is shown if its SELECT is shown, ignoring the select's opacity
+    if (isElement(e, 'OPTION') || isElement(e, 'OPTGROUP')) {
+      var select = getAncestor(e, function (a) { return isElement(a, 'SELECT'); });
+      return !!select && isShown(select, true);
+    }
+    // An image map element is shown if the image using it is shown and its area is positive
+    var imageMap = maybeFindImageMap(e);
+    if (imageMap) {
+      return !!imageMap.image && imageMap.rect.width > 0 && imageMap.rect.height > 0 &&
+        isShown(imageMap.image, ignoreOpacity);
+    }
+    if (isElement(e, 'INPUT') && e.type.toLowerCase() == 'hidden') {
+      return false;
+    }
+    if (isElement(e, 'NOSCRIPT')) {
+      return false;
+    }
+    var visibility = getEffectiveStyle(e, 'visibility');
+    if (visibility == 'collapse' || visibility == 'hidden') {
+      return false;
+    }
+    if (!displayed(e)) {
+      return false;
+    }
+    if (!ignoreOpacity && getOpacity(e) == 0) {
+      return false;
+    }
+
+    function positiveSize(node) {
+      var r = getClientRect(node);
+      if (r.height > 0 && r.width > 0) {
+        return true;
+      }
+      // A vertical or horizontal SVG path has a zero size, but is shown if it has a positive stroke width
+      if (isElement(node, 'PATH') && (r.height > 0 || r.width > 0)) {
+        var strokeWidth = getEffectiveStyle(node, 'stroke-width');
+        return !!strokeWidth && parseInt(strokeWidth, 10) > 0;
+      }
+      // A zero-sized element still has a positive size if it has a text or a positive-sized child,
+      // unless its overflow is hidden
+      return getEffectiveStyle(node, 'overflow') != 'hidden' &&
+        Array.prototype.some.call(node.childNodes, function (child) {
+          return child.nodeType == 3 || (isElement(child) && positiveSize(child));
+        });
+    }
+    if (!positiveSize(e)) {
+      return false;
+    }
+
+    function hiddenByOverflow(node) {
+      return getOverflowState(node) == OVERFLOW_HIDDEN &&
+        Array.prototype.every.call(node.childNodes, function (child) {
+          return !isElement(child) || hiddenByOverflow(child) || !positiveSize(child);
+        });
+    }
+    return !hiddenByOverflow(e);
+  }
+
+  return isShown(elem, false);
+}
@@ -4,24 +4,33 @@ use self::utils::*;
 use super::actions::*;
 use super::capabilities::*;
 use super::chromeoptions::*;
+use super::driverservice::*;
 use super::element::*;
+use super::error::*;
 use super::firefoxoptions::*;
 use super::reqs::*;
 use super::safarioptions::*;
+#[cfg(feature = "image")]
+use super::screenshot::*;
+use super::script::*;
+use super::serverurl::*;
 use super::specialkey::*;
+use super::transport::*;
+use super::wirelog::*;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-#[derive(Serialize, Deserialize)]
-struct Value {
-    value: Session,
-}
 #[allow(non_snake_case)]
-#[derive(Serialize, Deserialize)]
-struct Session {
-    sessionId: String,
+#[derive(Deserialize)]
+pub(crate) struct Session {
+    pub(crate) sessionId: String,
+    #[serde(default)]
+    pub(crate) capabilities: SessionCapabilities,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BrowserName {
     Chrome,
     Firefox,
@@ -33,8 +42,7 @@ pub enum BrowserName {
 /// All methods that create local sessions imply that the selenium server is running on port 4444
 #[derive(Debug)]
 pub struct Browser {
-    ip: String,
-    port: String,
+    transport: Arc<SessionTransport<dyn Transport>>,
     session_url: String, //The session/ url for constructing other urls
     go_to_url: String,   //The url to a website of the test
     timeouts_url: String,
@@ -64,13 +72,16 @@ pub struct Browser {
     alert_text_url: String,
     screenshot_url: String,
     print_page_url: String,
+    capabilities: SessionCapabilities,
+    driver: Option<DriverProcess>, //The local driver started by the DriverService
+    detached: bool,                //If set, the session is left open on drop
 }
 
 impl Browser {
     /// Method to construct the Browser instance with basic session. Supports args for Chrome,
     /// while for Firefox and Safari they will be ignored. To customize the Chrome and Firefox sessions pls use the corresponding
     /// method start_..._session_with_options().
-    ///  
+    ///
     /// # Examples
     /// ```
     /// # use selenium_webdriver::*;
@@ -79,19 +90,7 @@ impl Browser {
     /// ```
     pub fn start_session(browser: BrowserName, args: Vec<&str>) -> Browser {
         let req_body = create_session_body_json(browser, args);
-        let response = send_request(
-            "127.0.0.1",
-            "4444",
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&req_body),
-            &req_body,
-        )
-        .unwrap();
-        let resp_body = resp_body(response).unwrap();
-        let val: Value = serde_json::from_str(&resp_body).unwrap();
-        let sess_id = val.value.sessionId;
-        generate_browser_links("127.0.0.1", "4444", &sess_id)
+        session_at(&ServerUrl::default(), &req_body, None).unwrap()
     }
     ///Allows to create a customized session with various capabilities. For details please check the docs for the Capabilities struct and its methods.
     ///
@@ -114,42 +113,74 @@ impl Browser {
     /// assert!(res.is_ok());
     /// std::fs::remove_file("vk.png").unwrap();
     /// ```
-    pub fn start_session_with_capabilities(capabilities: Capabilities) -> Result<Browser, String> {
-        let body = capabilities.cap_string;
-        let response = send_request(
-            "127.0.0.1",
-            "4444",
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&body),
-            &body,
+    pub fn start_session_with_capabilities(
+        capabilities: Capabilities,
+    ) -> Result<Browser, WebDriverError> {
+        session_at(
+            &ServerUrl::default(),
+            &capabilities.cap_string,
+            capabilities.observer,
         )
-        .unwrap();
-        let resp_body = resp_body(response).unwrap();
-        let val: Value = serde_json::from_str(&resp_body).unwrap();
-        let sess_id = val.value.sessionId;
-        Ok(generate_browser_links("127.0.0.1", "4444", &sess_id))
     }
     ///Does the same thing as the start_session_with_capabilities(),but for the remote session.
     pub fn start_remote_session_with_capabilities(
         capabilities: Capabilities,
         ip: &str,
         port: &str,
-    ) -> Result<Browser, String> {
-        let body = capabilities.cap_string;
-        let response = send_request(
-            ip,
-            port,
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&body),
-            &body,
+    ) -> Result<Browser, WebDriverError> {
+        session_at(
+            &remote_url(ip, port)?,
+            &capabilities.cap_string,
+            capabilities.observer,
+        )
+    }
+    ///Creates the session on the server at the given url. The url defines the path prefix of all the commands,
+    /// so the crate can talk to the Selenium 4 Grid or directly to a driver.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use selenium_webdriver::*;
+    /// let url: ServerUrl = "http://localhost:9515".parse().unwrap();
+    /// let c = Capabilities::new(BrowserName::Chrome, "linux");
+    /// let mut br = Browser::start_session_with_url(&url, c).unwrap();
+    /// br.open("https://vk.com").unwrap();
+    /// br.close_browser().unwrap();
+    /// ```
+    pub fn start_session_with_url(
+        url: &ServerUrl,
+        capabilities: Capabilities,
+    ) -> Result<Browser, WebDriverError> {
+        session_at(url, &capabilities.cap_string, capabilities.observer)
+    }
+    ///Starts the driver with the DriverService and creates the session on it without the selenium server.
+    /// The driver process is killed when the browser is closed or dropped.
+    pub fn start_session_with_service(
+        service: &DriverService,
+        capabilities: Capabilities,
+    ) -> Result<Browser, WebDriverError> {
+        let driver = service.start()?;
+        let mut browser = session_at(
+            driver.get_url(),
+            &capabilities.cap_string,
+            capabilities.observer,
+        )?;
+        //The driver runs on this machine and does not support "se/file"
+        browser.transport.set_local(true);
+        browser.driver = Some(driver);
+        Ok(browser)
+    }
+    ///Creates the session using a custom transport instead of the TCP connection to the selenium server,
+    /// e.g. the MockTransport to test the code using the Browser without the server.
+    pub fn start_session_with_transport<T: Transport + 'static>(
+        transport: T,
+        capabilities: Capabilities,
+    ) -> Result<Browser, WebDriverError> {
+        new_session(
+            Arc::new(transport),
+            &ServerUrl::default(),
+            &capabilities.cap_string,
+            capabilities.observer,
         )
-        .unwrap();
-        let resp_body = resp_body(response).unwrap();
-        let val: Value = serde_json::from_str(&resp_body).unwrap();
-        let sess_id = val.value.sessionId;
-        Ok(generate_browser_links(ip, port, &sess_id))
     }
     ///Method to construct the Browser instance with basic remote session. Also intended to add chrome/firefox/safari options to the remote sessions.
     pub fn start_remote_session(
@@ -157,39 +188,9 @@ impl Browser {
         platform: &str,
         ip: &str,
         port: &str,
-    ) -> Result<Browser, String> {
-        let browser = match browser {
-            BrowserName::Chrome => "chrome",
-            BrowserName::Firefox => "firefox",
-            BrowserName::Safari => "safari",
-        };
-        let req_body = format!(
-            r#"{{
-            "capabilities": {{
-                "alwaysMatch": {{
-                    "platformName": "{}"
-                }},
-                "firstMatch": [
-                    {{"browserName": "{}"}}
-                ]
-            }}
-        }}
-        "#,
-            platform, browser
-        );
-        let response = send_request(
-            ip,
-            port,
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&req_body),
-            &req_body,
-        )
-        .unwrap();
-        let resp_body = resp_body(response).unwrap();
-        let val: Value = serde_json::from_str(&resp_body).unwrap();
-        let sess_id = val.value.sessionId;
-        Ok(generate_browser_links(ip, port, &sess_id))
+    ) -> Result<Browser, WebDriverError> {
+        let req_body = create_remote_session_body(browser, platform);
+        session_at(&remote_url(ip, port)?, &req_body, None)
     }
     ///Method to start the session customized with ChromeOptions
     ///
@@ -206,380 +207,187 @@ impl Browser {
     /// assert!(res.is_ok()&&res2.is_ok());
     ///
     /// ```
-    pub fn start_chrome_session_with_options(options: ChromeOptions) -> Result<Browser, String> {
+    pub fn start_chrome_session_with_options(
+        options: ChromeOptions,
+    ) -> Result<Browser, WebDriverError> {
         let body = create_json_body_for_session_with_chrome_options(options);
-        let resp = send_and_read_body(
-            "127.0.0.1",
-            "4444",
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let val: Value = serde_json::from_str(&resp).unwrap();
-        let sess_id = val.value.sessionId;
-        Ok(generate_browser_links("127.0.0.1", "4444", &sess_id))
+        session_at(&ServerUrl::default(), &body, None)
     }
     /// Method to start the Firefox session adjusted with FirefoxOptions
     /// Works similar to the ChromeOptions. For more info please check the FirefoxOptions docs.
-    pub fn start_firefox_session_with_options(options: FirefoxOptions) -> Result<Browser, String> {
+    pub fn start_firefox_session_with_options(
+        options: FirefoxOptions,
+    ) -> Result<Browser, WebDriverError> {
         let body = create_json_body_for_session_with_firefox_options(options);
-        let resp = send_and_read_body(
-            "127.0.0.1",
-            "4444",
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let val: Value = serde_json::from_str(&resp).unwrap();
-        let sess_id = val.value.sessionId;
-        Ok(generate_browser_links("127.0.0.1", "4444", &sess_id))
+        session_at(&ServerUrl::default(), &body, None)
     }
     /// Method to start the Safari with settings.
     /// Works similar to the ChromeOptions and FFOptions. For more info please check the SafariOptions docs.
-    pub fn start_safari_session_with_options(options: SafariOptions) -> Result<Browser, String> {
+    pub fn start_safari_session_with_options(
+        options: SafariOptions,
+    ) -> Result<Browser, WebDriverError> {
         let body = create_json_body_for_session_with_safari_options(options);
-        let resp = send_and_read_body(
-            "127.0.0.1",
-            "4444",
-            Method::POST,
-            "wd/hub/session",
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let val: Value = serde_json::from_str(&resp).unwrap();
-        let sess_id = val.value.sessionId;
-        Ok(generate_browser_links("127.0.0.1", "4444", &sess_id))
+        session_at(&ServerUrl::default(), &body, None)
     }
     ///Open a webpage or a local file
-    pub fn open(&self, uri: &str) -> Result<(), String> {
-        let body = format!(r#"{{"url":"{}"}}"#, uri);
-        let resp = send_request(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.go_to_url,
-            cont_length_header(&body),
-            &body,
-        )
-        .unwrap();
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn open(&self, uri: &str) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "url": uri }).to_string();
+        send_command(&*self.transport, Method::POST, &self.go_to_url, &body)?;
         Ok(())
     }
     ///Get the url of the current page.
-    pub fn get_link(&self) -> Result<String, String> {
-        let resp = resp_body(
-            send_request(
-                &self.ip,
-                &self.port,
-                Method::GET,
-                &self.go_to_url,
-                vec![],
-                "",
-            )
-            .unwrap(),
-        )
-        .unwrap();
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        Ok(parse_value(&resp).replace("\"", ""))
-    }
-    ///Deletes current session. As the Browser struct needs dropping,
-    ///it is better to call drop() after this method in long-multibrowser scenarios.  
-    pub fn close_browser(&mut self) -> Result<(), String> {
-        let resp = send_request(
-            &self.ip,
-            &self.port,
-            Method::DELETE,
-            &self.session_url,
-            vec![],
-            "",
-        )
-        .unwrap();
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn get_link(&self) -> Result<String, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.go_to_url, "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Deletes current session. After that the Browser and its Elements return
+    /// WebDriverError::SessionClosed without sending anything to the server.
+    ///
+    /// If the Browser is dropped without calling this method, the session is deleted in drop()
+    /// (errors are ignored there), unless the Browser was detached.
+    pub fn close_browser(&mut self) -> Result<(), WebDriverError> {
+        send_command(&*self.transport, Method::DELETE, &self.session_url, "")?;
+        self.transport.close();
         self.session_url = String::from("");
+        self.driver = None;
         Ok(())
     }
+    ///The capabilities negotiated with the server when the session was created
+    pub fn capabilities(&self) -> &SessionCapabilities {
+        &self.capabilities
+    }
+    ///Checks whether the selenium server at ip:port is ready to create new sessions
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use selenium_webdriver::*;
+    /// let status = Browser::server_status("127.0.0.1", "4444").unwrap();
+    /// assert!(status.is_ready(), "{}", status.get_message());
+    /// ```
+    pub fn server_status(ip: &str, port: &str) -> Result<ServerStatus, WebDriverError> {
+        Browser::server_status_at(&remote_url(ip, port)?)
+    }
+    ///Does the same as server_status() for the server at any url, e.g. a driver started by the DriverService
+    pub fn server_status_at(url: &ServerUrl) -> Result<ServerStatus, WebDriverError> {
+        server_status_with(&*tcp_transport(url), url)
+    }
+    ///Sets the observer notified about every request sent by this Browser and its Elements,
+    /// e.g. the FileWireLogger. It replaces the previously set one.
+    /// To observe the new session request too, use Capabilities::set_wire_observer instead.
+    pub fn set_wire_observer<O: WireObserver + 'static>(&mut self, observer: O) -> &mut Self {
+        self.transport.set_observer(Arc::new(observer));
+        self
+    }
+    ///Tells whether the browser runs on this machine, so Element::upload_file types the local paths
+    /// instead of sending the files to the server. False by default, even for the servers on localhost
+    /// (e.g. a Selenium server in a Docker container), and true for the sessions started with the DriverService.
+    pub fn set_local_file_detector(&mut self, local: bool) -> &mut Self {
+        self.transport.set_local(local);
+        self
+    }
+    ///Keeps the session open on the server when the Browser is dropped,
+    /// e.g. to inspect the browser state after a failed test
+    pub fn detach(&mut self) {
+        self.detached = true;
+    }
     ///Returns the session timouts data
-    pub fn get_timeouts(&self) -> Result<Timeouts, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.timeouts_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        Ok(serde_json::from_str(&parse_value(&resp)).unwrap())
+    pub fn get_timeouts(&self) -> Result<Timeouts, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.timeouts_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Change the session timouts data
-    pub fn set_timeouts(&self, timeouts: &Timeouts) -> Result<(), &str> {
-        let timeouts_json = serde_json::to_string(timeouts).unwrap();
-        if let Ok(mess) = send_request(
-            &self.ip,
-            &self.port,
+    pub fn set_timeouts(&self, timeouts: &Timeouts) -> Result<(), WebDriverError> {
+        let timeouts_json = serde_json::to_string(timeouts)?;
+        send_command(
+            &*self.transport,
             Method::POST,
             &self.timeouts_url,
-            cont_length_header(&timeouts_json),
             &timeouts_json,
-        ) {
-            if let Ok(body) = resp_body(mess) {
-                if body.as_str() == r#"{"value":null}"# {
-                    return Ok(());
-                }
-            }
-        }
-        Err("The timeouts were not set correctly")
+        )?;
+        Ok(())
     }
     ///Return to the previous page
-    pub fn back(&self) -> Result<(), String> {
+    pub fn back(&self) -> Result<(), WebDriverError> {
         let body = r#"{"return":true}"#;
-        let resp = send_request(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.back_url,
-            cont_length_header(&body),
-            &body,
-        )
-        .unwrap();
-        if resp.contains("error") {
-            return Err(resp);
-        }
+        send_command(&*self.transport, Method::POST, &self.back_url, body)?;
         Ok(())
     }
-    pub fn forward(&self) -> Result<(), String> {
+    pub fn forward(&self) -> Result<(), WebDriverError> {
         let body = r#"{"forward":true}"#;
-        let resp = send_request(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.forward_url,
-            cont_length_header(&body),
-            &body,
-        )
-        .unwrap();
-        if resp.contains("error") {
-            return Err(resp);
-        }
+        send_command(&*self.transport, Method::POST, &self.forward_url, body)?;
         Ok(())
     }
-    pub fn refresh(&self) -> Result<(), &str> {
+    pub fn refresh(&self) -> Result<(), WebDriverError> {
         let body = r#"{"refresh":true}"#;
-        if let Ok(mess) = send_request(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.refresh_url,
-            cont_length_header(&body),
-            &body,
-        ) {
-            if let Ok(body) = resp_body(mess) {
-                if body.as_str() != r#"{"value":null}"# {
-                    return Err("The refresh did not succeed");
-                }
-            }
-        }
+        send_command(&*self.transport, Method::POST, &self.refresh_url, body)?;
         Ok(())
     }
     ///Returns the title of the current tab
-    pub fn get_title(&self) -> Result<String, String> {
-        let json = resp_body(
-            send_request(
-                &self.ip,
-                &self.port,
-                Method::GET,
-                &self.title_url,
-                vec![],
-                "",
-            )
-            .unwrap(),
-        )
-        .unwrap();
-        if json.contains("error") {
-            return Err(json);
-        }
-        Ok(parse_value(&json).replace("\"", ""))
+    pub fn get_title(&self) -> Result<String, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.title_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Returns the handle of the current window, which later may be used to switch to this window.
-    pub fn get_window_handle(&self) -> Result<String, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.window_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        Ok(parse_value(&resp).replace("\"", ""))
+    pub fn get_window_handle(&self) -> Result<String, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.window_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Returns the handles of all open windows and tabs
-    pub fn get_window_handles(&self) -> Result<Vec<String>, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.window_handles_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let res: Vec<String> = serde_json::from_str(&resp).unwrap();
-        Ok(res)
+    pub fn get_window_handles(&self) -> Result<Vec<String>, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.window_handles_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Switches to the window with the passed id
-    pub fn switch_to_window(&self, window_id: String) -> Result<(), String> {
-        let body = format!(r#"{{"handle":"{}"}}"#, window_id);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.window_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.as_str() == r#"{"value":null}"# {
-            Ok(())
-        } else {
-            Err(resp)
-        }
+    pub fn switch_to_window(&self, window_id: String) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "handle": window_id }).to_string();
+        send_command(&*self.transport, Method::POST, &self.window_url, &body)?;
+        Ok(())
     }
     ///Opens a new window or a new tab depending on the window_type
-    pub fn new_window(&self, window_type: NewWindowType) -> Result<(String, String), String> {
+    pub fn new_window(
+        &self,
+        window_type: NewWindowType,
+    ) -> Result<(String, String), WebDriverError> {
         let body = match window_type {
             NewWindowType::Tab => r#"{"type":"tab"}"#,
             NewWindowType::Window => r#"{"type":"window"}"#,
         };
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.window_new_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
+        let resp = send_command(&*self.transport, Method::POST, &self.window_new_url, body)?;
+        let map: HashMap<String, String> = serde_json::from_value(resp)?;
+        match (map.get("handle"), map.get("type")) {
+            (Some(handle), Some(wtype)) => Ok((handle.clone(), wtype.clone())),
+            _ => Err(WebDriverError::InvalidResponse(String::from(
+                "The new window handle or type is missing",
+            ))),
         }
-        let resp = parse_value(&resp);
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        let handle = map.get("handle").unwrap().clone();
-        let wtype = map.get("type").unwrap().clone();
-        Ok((handle, wtype))
     }
     ///Closes the window and returns the vector of the remaining window handles
-    pub fn close_window(&self) -> Result<Vec<String>, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::DELETE,
-            &self.window_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let res: Vec<String> = serde_json::from_str(&resp).unwrap();
-        Ok(res)
+    pub fn close_window(&self) -> Result<Vec<String>, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::DELETE, &self.window_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Switches to the frame with a given id. For instance, if there are 4 frames and you wish to switch to the second one,
     /// you should call this method like this - switch_to_frame_by_id(1).
-    pub fn switch_to_frame_by_id(&self, id: u64) -> Result<(), String> {
+    pub fn switch_to_frame_by_id(&self, id: u64) -> Result<(), WebDriverError> {
         let body = format!(r#"{{"id":{}}}"#, id);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.frame_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.as_str() != r#"{"value":null}"# {
-            return Err(resp);
-        }
+        send_command(&*self.transport, Method::POST, &self.frame_url, &body)?;
         Ok(())
     }
-    pub fn switch_to_frame_by_element(&self, element: Element) -> Result<(), String> {
-        let body = format!(
-            r#"{{"id":{{"{}":"{}"}}}}"#,
-            element.element_gr_id, element.element_id
-        );
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.frame_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.as_str() != r#"{"value":null}"# {
-            return Err(resp);
-        }
+    pub fn switch_to_frame_by_element(&self, element: Element) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({
+            "id": { element.element_gr_id.as_str(): element.element_id() }
+        })
+        .to_string();
+        send_command(&*self.transport, Method::POST, &self.frame_url, &body)?;
         Ok(())
     }
-    pub fn switch_to_parent_frame(&self) -> Result<(), String> {
-        let body = r#"{}"#;
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.frame_parent_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.as_str() != r#"{"value":null}"# {
-            return Err(resp);
-        }
+    pub fn switch_to_parent_frame(&self) -> Result<(), WebDriverError> {
+        send_command(&*self.transport, Method::POST, &self.frame_parent_url, "{}")?;
         Ok(())
     }
-    pub fn get_active_element(&self) -> Result<Element, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.element_active_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let map: HashMap<String, String> = serde_json::from_str(&resp).unwrap();
-        let res = map.iter().next().unwrap();
-        Ok(Element {
-            ip: self.ip.clone(),
-            port: self.port.clone(),
-            element_gr_id: res.0.clone(),
-            element_id: res.1.clone(),
-            element_url: format!("{}/element/{}", self.session_url, res.1.clone()),
-        })
+    pub fn get_active_element(&self) -> Result<Element, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.element_active_url, "")?;
+        Element::from_reference(self.transport.clone(), &self.session_url, resp)
     }
     ///If the locator matches several elements, it returns the first one
     ///
@@ -594,231 +402,94 @@ impl Browser {
     ///br.close_browser().unwrap();
     ///assert!(res.is_ok());
     /// ```
-    pub fn find_element(&self, loc_strategy: LocatorStrategy) -> Result<Element, String> {
-        let body = body_for_find_element(loc_strategy);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.element_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let map: HashMap<String, String> = serde_json::from_str(&resp).unwrap();
-        let res = map.iter().next().unwrap();
-        Ok(Element {
-            ip: self.ip.clone(),
-            port: self.port.clone(),
-            element_gr_id: res.0.clone(),
-            element_id: res.1.clone(),
-            element_url: format!("{}/element/{}", self.session_url, res.1.clone()),
-        })
-    }
-    pub fn find_elements(&self, loc_strategy: LocatorStrategy) -> Result<Vec<Element>, String> {
-        let mut result = vec![];
-        let body = body_for_find_element(loc_strategy);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.elements_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let map: Vec<HashMap<String, String>> = serde_json::from_str(&resp).unwrap();
-        let element_ur = format!("{}/element", self.session_url);
-        for i in map {
-            let element_ur = element_ur.clone();
-            let res = i.iter().next().unwrap();
-            result.push(Element {
-                ip: self.ip.clone(),
-                port: self.port.clone(),
-                element_gr_id: res.0.clone(),
-                element_id: res.1.clone(),
-                element_url: format!("{}/{}", element_ur, res.1.clone()),
-            });
-        }
-        Ok(result)
+    pub fn find_element<S: AsRef<str>>(
+        &self,
+        loc_strategy: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        let body = body_for_find_element(&loc_strategy);
+        let resp = send_command(&*self.transport, Method::POST, &self.element_url, &body)?;
+        let element = Element::from_reference(self.transport.clone(), &self.session_url, resp)?;
+        Ok(element.located_by(vec![loc_strategy.to_owned_locator()]))
+    }
+    pub fn find_elements<S: AsRef<str>>(
+        &self,
+        loc_strategy: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        let body = body_for_find_element(&loc_strategy);
+        let resp = send_command(&*self.transport, Method::POST, &self.elements_url, &body)?;
+        let refs: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        refs.into_iter()
+            .map(|r| Element::from_reference(self.transport.clone(), &self.session_url, r))
+            .collect()
     }
     ///Returns the WindowRect instance which contains the information about the position and size of the current window
-    pub fn get_window_rect(&self) -> Result<WindowRect, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.window_rect_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, WindowRect> = serde_json::from_str(&resp).unwrap();
-        Ok(map.get("value").unwrap().clone())
+    pub fn get_window_rect(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.window_rect_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Allow to resize the window and change it's position
-    pub fn set_sindow_rect(&self, window_rect: &WindowRect) -> Result<WindowRect, String> {
-        let body = serde_json::to_string(window_rect).unwrap();
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.window_rect_url,
-            cont_length_header(&body),
-            &body,
-        );
-        let resp = parse_value(&resp);
-        let map: Result<WindowRect, serde_json::Error> = serde_json::from_str(&resp);
-        match map {
-            Ok(cont) => Ok(cont),
-            Err(message) => Err(message.to_string()),
-        }
-    }
-    pub fn maximize_window(&self) -> Result<WindowRect, String> {
-        let body = r#"{}"#;
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
+    pub fn set_sindow_rect(&self, window_rect: &WindowRect) -> Result<WindowRect, WebDriverError> {
+        let body = serde_json::to_string(window_rect)?;
+        let resp = send_command(&*self.transport, Method::POST, &self.window_rect_url, &body)?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub fn maximize_window(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = send_command(
+            &*self.transport,
             Method::POST,
             &self.window_maximize_url,
-            cont_length_header(&body),
-            &body,
-        );
-        let resp = parse_value(&resp);
-        if resp.contains("height") && resp.contains("width") {
-            let res: WindowRect = serde_json::from_str(&resp).unwrap();
-            Ok(res)
-        } else {
-            Err(resp)
-        }
+            "{}",
+        )?;
+        Ok(serde_json::from_value(resp)?)
     }
-    pub fn minimize_window(&self) -> Result<WindowRect, String> {
-        let body = r#"{}"#;
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
+    pub fn minimize_window(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = send_command(
+            &*self.transport,
             Method::POST,
             &self.window_minimize_url,
-            cont_length_header(&body),
-            &body,
-        );
-        let resp = parse_value(&resp);
-        if resp.contains("height") && resp.contains("width") {
-            let res: WindowRect = serde_json::from_str(&resp).unwrap();
-            Ok(res)
-        } else {
-            Err(resp)
-        }
+            "{}",
+        )?;
+        Ok(serde_json::from_value(resp)?)
     }
-    pub fn fullscreen(&self) -> Result<WindowRect, String> {
-        let body = r#"{}"#;
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
+    pub fn fullscreen(&self) -> Result<WindowRect, WebDriverError> {
+        let resp = send_command(
+            &*self.transport,
             Method::POST,
             &self.window_fullscreen_url,
-            cont_length_header(&body),
-            &body,
-        );
-        let resp = parse_value(&resp);
-        if resp.contains("height") {
-            Ok(serde_json::from_str(&resp).unwrap())
-        } else {
-            Err(resp)
-        }
+            "{}",
+        )?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Returns the page source code
-    pub fn source(&self) -> Result<String, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.source_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok(map.get("value").unwrap().clone())
+    pub fn source(&self) -> Result<String, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.source_url, "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Return the vector with all the cookies that the browser is holding at the moment
-    pub fn get_all_cookies(&self) -> Result<Vec<Cookie>, String> {
-        let mut result: Vec<Cookie> = vec![];
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.cookie_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, Vec<serde_json::Value>> = serde_json::from_str(&resp).unwrap();
-        for v in map.get("value").unwrap() {
-            result.push(from_value_to_cookie(v));
-        }
-        Ok(result)
+    pub fn get_all_cookies(&self) -> Result<Vec<Cookie>, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.cookie_url, "")?;
+        let cookies: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        cookies.iter().map(from_value_to_cookie).collect()
     }
     ///Returns the information on a particular cookie
-    pub fn get_cookie(&self, cookie_name: &str) -> Result<Cookie, String> {
+    pub fn get_cookie(&self, cookie_name: &str) -> Result<Cookie, WebDriverError> {
         let url = format!("{}/{}", self.cookie_url, cookie_name);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("domain") && resp.contains("expiry") && resp.contains("name") {
-            let map: HashMap<&str, serde_json::Value> = serde_json::from_str(&resp).unwrap();
-            let v = map.get(&"value").unwrap();
-            return Ok(from_value_to_cookie(v));
-        }
-        Err(resp)
-    }
-    pub fn add_cookie(&self, cookie: Cookie) -> Result<(), String> {
-        let cook = serde_json::to_string(&cookie).unwrap();
-        let body = format!(r#"{{"cookie": {} }}"#, cook);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.cookie_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp == r#"{"value":null}"# {
-            return Ok(());
-        }
-        Err(resp)
+        let resp = send_command(&*self.transport, Method::GET, &url, "")?;
+        from_value_to_cookie(&resp)
+    }
+    pub fn add_cookie(&self, cookie: Cookie) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "cookie": cookie }).to_string();
+        send_command(&*self.transport, Method::POST, &self.cookie_url, &body)?;
+        Ok(())
     }
-    pub fn delete_cookie(&self, cookie_name: &str) -> Result<(), String> {
+    pub fn delete_cookie(&self, cookie_name: &str) -> Result<(), WebDriverError> {
         let uri = format!("{}/{}", self.cookie_url, cookie_name);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::DELETE, &uri, vec![], "");
-        if resp == r#"{"value":null}"# {
-            return Ok(());
-        }
-        Err(resp)
-    }
-    pub fn delete_all_cookies(&self) -> Result<(), String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::DELETE,
-            &self.cookie_url,
-            vec![],
-            "",
-        );
-        if resp == r#"{"value":null}"# {
-            return Ok(());
-        }
-        Err(resp)
+        send_command(&*self.transport, Method::DELETE, &uri, "")?;
+        Ok(())
+    }
+    pub fn delete_all_cookies(&self) -> Result<(), WebDriverError> {
+        send_command(&*self.transport, Method::DELETE, &self.cookie_url, "")?;
+        Ok(())
     }
     ///The path should be absolute with the extension
     ///
@@ -831,139 +502,261 @@ impl Browser {
     /// br.close_browser().unwrap();
     /// # std::fs::remove_file("screen.png").unwrap();
     /// ```
-    pub fn take_screenshot(&self, path: &str) -> Result<(), String> {
-        if let Ok(resp) = send_request_screensh(Method::GET, &self.screenshot_url, vec![], "") {
-            if let Ok(new) = base64::decode(resp) {
-                match std::fs::write(path, new) {
-                    Ok(()) => return Ok(()),
-                    Err(message) => return Err(message.to_string()),
-                }
-            }
+    pub fn take_screenshot(&self, path: &str) -> Result<(), WebDriverError> {
+        let png = self.get_screenshot_png()?;
+        std::fs::write(path, png).map_err(WebDriverError::Io)
+    }
+    pub fn take_element_screenshot(
+        &self,
+        elem: &Element,
+        path: &str,
+    ) -> Result<(), WebDriverError> {
+        let png = self.get_element_screenshot_png(elem)?;
+        std::fs::write(path, png).map_err(WebDriverError::Io)
+    }
+    ///Returns the PNG image of the viewport without saving it, e.g. to attach it to a report
+    pub fn get_screenshot_png(&self) -> Result<Vec<u8>, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.screenshot_url, "")?;
+        decode_base64_value(resp)
+    }
+    ///Returns the PNG image of the element without saving it
+    pub fn get_element_screenshot_png(&self, elem: &Element) -> Result<Vec<u8>, WebDriverError> {
+        let uri = format!("{}/{}/screenshot", self.element_url, elem.element_id());
+        let resp = send_command(&*self.transport, Method::GET, &uri, "")?;
+        decode_base64_value(resp)
+    }
+    ///Saves the PNG image of the whole document, see get_full_page_screenshot_png
+    pub fn take_full_page_screenshot(&self, path: &str) -> Result<(), WebDriverError> {
+        let png = self.get_full_page_screenshot_png()?;
+        std::fs::write(path, png).map_err(WebDriverError::Io)
+    }
+    ///Returns the PNG image of the whole document, not only of the viewport
+    ///
+    /// Firefox captures it with its "moz/screenshot/full" command, Chrome and Edge with
+    /// the DevTools Page.captureScreenshot beyond the viewport. For the other browsers, or if
+    /// the server does not know these commands, the page is scrolled and the viewport screenshots
+    /// are stitched together, which needs the "image" feature (the UnsupportedOperation error without it).
+    /// The fixed and sticky elements appear on every stitched viewport.
+    pub fn get_full_page_screenshot_png(&self) -> Result<Vec<u8>, WebDriverError> {
+        if let Some(png) = self.native_full_page_screenshot()? {
+            return Ok(png);
+        }
+        #[cfg(feature = "image")]
+        {
+            let image = self.stitch_full_page_screenshot()?;
+            encode_image(&image, ScreenshotFormat::Png)
         }
-        Err(String::from("Could not take a screenshot"))
-    }
-    pub fn take_element_screenshot(&self, elem: &Element, path: &str) -> Result<(), String> {
-        let uri = format!("{}/{}/screenshot", self.element_url, elem.element_id);
-        if let Ok(resp) = send_request_screensh(Method::GET, &uri, vec![], "") {
-            if let Ok(new) = base64::decode(resp) {
-                match std::fs::write(path, new) {
-                    Ok(()) => return Ok(()),
-                    Err(message) => return Err(message.to_string()),
-                }
+        #[cfg(not(feature = "image"))]
+        {
+            Err(WebDriverError::from_details(ErrorDetails {
+                error: String::from("unsupported operation"),
+                message: format!(
+                    "The full page screenshots of {} need the image feature",
+                    self.capabilities.get_browser_name()
+                ),
+                stacktrace: String::new(),
+                data: None,
+            }))
+        }
+    }
+    //The full page PNG made by the browser itself, None if it can not make one
+    pub(crate) fn native_full_page_screenshot(&self) -> Result<Option<Vec<u8>>, WebDriverError> {
+        let result = match self.capabilities.get_browser_name() {
+            "firefox" => {
+                let url = format!("{}/moz/screenshot/full", self.session_url);
+                send_command(&*self.transport, Method::GET, &url, "")
             }
-        }
-        Err(String::from("Could not take a screenshot"))
+            "chrome" => self.cdp_full_page_screenshot("goog"),
+            "msedge" | "MicrosoftEdge" => self.cdp_full_page_screenshot("ms"),
+            _ => return Ok(None),
+        };
+        match result {
+            Ok(data) => Ok(Some(decode_base64_value(data)?)),
+            Err(WebDriverError::UnknownCommand(_))
+            | Err(WebDriverError::UnknownMethod(_))
+            | Err(WebDriverError::UnsupportedOperation(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    //The CDP command endpoint has the vendor prefix of the driver, "goog" or "ms" for Edge
+    fn cdp_full_page_screenshot(&self, vendor: &str) -> Result<serde_json::Value, WebDriverError> {
+        let url = format!("{}/{}/cdp/execute", self.session_url, vendor);
+        let body = serde_json::json!({ "cmd": "Page.getLayoutMetrics", "params": {} });
+        let metrics = send_command(&*self.transport, Method::POST, &url, &body.to_string())?;
+        //Chrome before 92 has no cssContentSize, only contentSize
+        let size = match &metrics["cssContentSize"] {
+            serde_json::Value::Null => &metrics["contentSize"],
+            size => size,
+        };
+        let body = serde_json::json!({
+            "cmd": "Page.captureScreenshot",
+            "params": {
+                "captureBeyondViewport": true,
+                "clip": {"x": 0, "y": 0, "width": size["width"], "height": size["height"], "scale": 1}
+            }
+        });
+        let resp = send_command(&*self.transport, Method::POST, &url, &body.to_string())?;
+        Ok(resp["data"].clone())
     }
     /// Executes the sync fun in the browser. In case the argument is a string, it should be a raw string or should incluse escapes with double quotes
     /// For example, if the args list you want to pass is [5,"Jack", 15], the vector should be ["5",r#"Jack"#,"15"]
-    pub fn execute_sync(&self, script: &str, args: &Vec<&str>) -> Result<String, String> {
+    ///
+    /// Returns the json representation of the value returned by the script.
+    /// The script is not escaped, see execute and execute_script for the scripts with quotes or newlines.
+    pub fn execute_sync(&self, script: &str, args: &Vec<&str>) -> Result<String, WebDriverError> {
         let args = gen_script_args(args);
         let body = format!(r#"{{"script":"{}","args":{}}}"#, script, args);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
+        let resp = send_command(
+            &*self.transport,
             Method::POST,
             &self.execute_sync_url,
-            cont_length_header(&body),
             &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        Ok(resp)
+        )?;
+        Ok(resp.to_string())
     }
     ///Executes the async fun in the browser. The args should be passed similarly to the execute_sync fn.
-    pub fn execute_async(&self, script: &str, args: &Vec<&str>) -> Result<String, String> {
+    pub fn execute_async(&self, script: &str, args: &Vec<&str>) -> Result<String, WebDriverError> {
         let args = gen_script_args(args);
         let body = format!(r#"{{"script":"{}","args":{}}}"#, script, args);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
+        let resp = send_command(
+            &*self.transport,
             Method::POST,
             &self.execute_async_url,
-            cont_length_header(&body),
             &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        Ok(resp)
+        )?;
+        Ok(resp.to_string())
+    }
+    ///Executes the sync fun in the browser with the args, which may be the elements
+    ///
+    /// The elements in the returned value (e.g. from document.querySelectorAll) become the Elements.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use selenium_webdriver::*;
+    /// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+    /// br.open("https://vk.com").unwrap();
+    /// let footer = br.find_element(LocatorStrategy::CSS("footer")).unwrap();
+    /// br.execute_script("arguments[0].scrollIntoView()", &[ScriptArg::from(&footer)]).unwrap();
+    /// let links = br.execute_script("return document.querySelectorAll('a')", &[]).unwrap();
+    /// assert!(links.into_elements().unwrap().len() > 0);
+    /// ```
+    pub fn execute_script(
+        &self,
+        script: &str,
+        args: &[ScriptArg],
+    ) -> Result<ScriptValue, WebDriverError> {
+        self.run_script(&self.execute_sync_url, script, args)
+    }
+    ///Executes the async fun in the browser, see execute_script. The fun gets the callback as its last argument.
+    pub fn execute_async_script(
+        &self,
+        script: &str,
+        args: &[ScriptArg],
+    ) -> Result<ScriptValue, WebDriverError> {
+        self.run_script(&self.execute_async_url, script, args)
+    }
+    fn run_script(
+        &self,
+        url: &str,
+        script: &str,
+        args: &[ScriptArg],
+    ) -> Result<ScriptValue, WebDriverError> {
+        let body = script_body(script, args)?;
+        let resp = send_command(&*self.transport, Method::POST, url, &body)?;
+        ScriptValue::from_json(resp, self.transport.clone(), &self.session_url)
+    }
+    ///Executes the sync fun in the browser and deserializes the returned value into T
+    ///
+    /// The args may be anything serializable: a tuple, an array or a Vec are passed as the list
+    /// of arguments, () as no arguments and any other value as the only argument.
+    /// The elements are serialized as the element references, so they may be among the args too.
+    /// If the script throws, the JavascriptError with the message and the stacktrace
+    /// of the exception is returned, if the value does not match T, the Json error.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use selenium_webdriver::*;
+    /// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+    /// br.open("https://vk.com").unwrap();
+    /// let sum: i64 = br.execute("return arguments[0] + arguments[1]", (2, 3)).unwrap();
+    /// assert_eq!(sum, 5);
+    /// let title: String = br.execute("return document.title", ()).unwrap();
+    /// let body = br.find_element(LocatorStrategy::TAGNAME("body")).unwrap();
+    /// let tag: String = br.execute("return arguments[0].tagName", [&body]).unwrap();
+    /// ```
+    pub fn execute<T: DeserializeOwned, A: Serialize>(
+        &self,
+        script: &str,
+        args: A,
+    ) -> Result<T, WebDriverError> {
+        let body = script_body(script, args)?;
+        let resp = send_command(
+            &*self.transport,
+            Method::POST,
+            &self.execute_sync_url,
+            &body,
+        )?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Executes the async fun in the browser and deserializes the value it passes to the callback
+    /// (the last argument) into T, see execute for the args
+    pub fn execute_async_as<T: DeserializeOwned, A: Serialize>(
+        &self,
+        script: &str,
+        args: A,
+    ) -> Result<T, WebDriverError> {
+        let body = script_body(script, args)?;
+        let resp = send_command(
+            &*self.transport,
+            Method::POST,
+            &self.execute_async_url,
+            &body,
+        )?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Prints out the page. If you want to print it to pdf, use headless mode. The structs PrintSettings,Page and Margin allow you to customize the print.
-    pub fn print(&self, print_settings: &PrintSettings, path: &str) -> Result<(), String> {
-        let pr_set_body = serde_json::to_string(&print_settings).unwrap();
-        let resp = send_request_screensh(
+    pub fn print(&self, print_settings: &PrintSettings, path: &str) -> Result<(), WebDriverError> {
+        let pr_set_body = serde_json::to_string(&print_settings)?;
+        let resp = send_command(
+            &*self.transport,
             Method::POST,
             &self.print_page_url,
-            cont_length_header(&pr_set_body),
             &pr_set_body,
-        )
-        .unwrap();
-        let new = base64::decode(resp).unwrap();
-        std::fs::write(path, new).unwrap();
-        Ok(())
+        )?;
+        let new = decode_base64_value(resp)?;
+        std::fs::write(path, new).map_err(WebDriverError::Io)
     }
 }
 
+impl Drop for Browser {
+    fn drop(&mut self) {
+        if !self.detached && !self.transport.is_closed() {
+            let _ = send_command(&*self.transport, Method::DELETE, &self.session_url, "");
+            self.transport.close();
+        }
+    }
+}
 impl Browser {
-    pub fn dismiss_alert(&self) -> Result<(), String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
+    pub fn dismiss_alert(&self) -> Result<(), WebDriverError> {
+        send_command(
+            &*self.transport,
             Method::POST,
             &self.alert_dismiss_url,
-            cont_length_header("{}"),
             "{}",
-        );
-        if resp == r#"{"value":null}"# {
-            Ok(())
-        } else {
-            Err(resp)
-        }
+        )?;
+        Ok(())
     }
-    pub fn allow_alert(&self) -> Result<(), String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.alert_accept_url,
-            cont_length_header("{}"),
-            "{}",
-        );
-        if resp == r#"{"value":null}"# {
-            Ok(())
-        } else {
-            Err(resp)
-        }
+    pub fn allow_alert(&self) -> Result<(), WebDriverError> {
+        send_command(&*self.transport, Method::POST, &self.alert_accept_url, "{}")?;
+        Ok(())
     }
-    pub fn get_alert_text(&self) -> Result<String, String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::GET,
-            &self.alert_text_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).to_string())
-    }
-    pub fn send_alert_text(&self, text: &str) -> Result<(), String> {
-        let body = format!(r#"{{"text":{}}}"#, text);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.alert_dismiss_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn get_alert_text(&self) -> Result<String, WebDriverError> {
+        let resp = send_command(&*self.transport, Method::GET, &self.alert_text_url, "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub fn send_alert_text(&self, text: &str) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "text": text }).to_string();
+        send_command(&*self.transport, Method::POST, &self.alert_text_url, &body)?;
         Ok(())
     }
     /// Pls see the Actions struct page to learn how to properly construct the Actions instance.
@@ -981,79 +774,127 @@ impl Browser {
     /// br.perform_actions(actions);
     /// br.close_browser().unwrap();
     /// ```
-    pub fn perform_actions(&self, actions: Actions) -> Result<(), String> {
+    pub fn perform_actions(&self, actions: Actions) -> Result<(), WebDriverError> {
         let mut actions = actions;
         actions.set_ids();
-        let body = serde_json::to_string(&actions).unwrap();
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &self.actions_url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
+        let body = serde_json::to_string(&actions)?;
+        send_command(&*self.transport, Method::POST, &self.actions_url, &body)?;
         Ok(())
     }
     ///Releases all the actions present in the current session's internal state.
     ///While the key actions are performed just after calling the perform_actions method,
     ///for instance, mouse actions are performed only after calling this method.
-    pub fn release_actions(&self) -> Result<(), String> {
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::DELETE,
-            &self.actions_url,
-            vec![],
-            "",
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn release_actions(&self) -> Result<(), WebDriverError> {
+        send_command(&*self.transport, Method::DELETE, &self.actions_url, "")?;
         Ok(())
     }
 }
-pub(self) mod utils {
+pub(crate) mod utils {
     use super::*;
-    pub(super) fn generate_browser_links(ip: &str, port: &str, sess_id: &str) -> Browser {
+    pub(super) fn tcp_transport(url: &ServerUrl) -> Arc<dyn Transport> {
+        Arc::new(TcpTransport::new(
+            url.get_host(),
+            &url.get_port().to_string(),
+        ))
+    }
+    ///The url of the selenium server 3 used by the start_remote_session... methods
+    pub(crate) fn remote_url(ip: &str, port: &str) -> Result<ServerUrl, WebDriverError> {
+        ServerUrl::parse(&format!("http://{}:{}/wd/hub", ip, port))
+    }
+    pub(super) fn session_at(
+        url: &ServerUrl,
+        body: &str,
+        observer: Option<Arc<dyn WireObserver>>,
+    ) -> Result<Browser, WebDriverError> {
+        new_session(tcp_transport(url), url, body, observer)
+    }
+    pub(crate) fn server_status_with(
+        transport: &dyn Transport,
+        url: &ServerUrl,
+    ) -> Result<ServerStatus, WebDriverError> {
+        let resp = send_command(transport, Method::GET, &url.endpoint("status"), "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub(super) fn new_session(
+        transport: Arc<dyn Transport>,
+        url: &ServerUrl,
+        body: &str,
+        observer: Option<Arc<dyn WireObserver>>,
+    ) -> Result<Browser, WebDriverError> {
+        //The observer is set before the new session request, so it sees the request too
+        let transport = Arc::new(SessionTransport::new(transport));
+        if let Some(observer) = observer {
+            transport.set_observer(observer);
+        }
+        let resp = send_command(&*transport, Method::POST, &url.endpoint("session"), body)?;
+        let session: Session = serde_json::from_value(resp)?;
+        let session_url = url.endpoint(&format!("session/{}", session.sessionId));
+        let mut browser = generate_browser_links(transport, session_url);
+        browser.capabilities = session.capabilities;
+        Ok(browser)
+    }
+    pub(super) fn generate_browser_links(
+        transport: Arc<SessionTransport<dyn Transport>>,
+        session_url: String,
+    ) -> Browser {
         Browser {
-            port: String::from(port),
-            ip: String::from(ip),
-            session_url: format!("wd/hub/session/{}", sess_id),
-            go_to_url: format!("wd/hub/session/{}/url", sess_id),
-            timeouts_url: format!("wd/hub/session/{}/timeouts", sess_id),
-            back_url: format!("wd/hub/session/{}/back", sess_id),
-            forward_url: format!("wd/hub/session/{}/forward", sess_id),
-            refresh_url: format!("wd/hub/session/{}/refresh", sess_id),
-            title_url: format!("wd/hub/session/{}/title", sess_id),
-            window_url: format!("wd/hub/session/{}/window", sess_id),
-            window_handles_url: format!("wd/hub/session/{}/window/handles", sess_id),
-            window_new_url: format!("wd/hub/session/{}/window/new", sess_id),
-            window_rect_url: format!("wd/hub/session/{}/window/rect", sess_id),
-            frame_url: format!("wd/hub/session/{}/frame", sess_id),
-            frame_parent_url: format!("wd/hub/session/{}/frame/parent", sess_id),
-            window_maximize_url: format!("wd/hub/session/{}/window/maximize", sess_id),
-            window_minimize_url: format!("wd/hub/session/{}/window/minimize", sess_id),
-            window_fullscreen_url: format!("wd/hub/session/{}/window/fullscreen", sess_id),
-            element_url: format!("wd/hub/session/{}/element", sess_id),
-            element_active_url: format!("wd/hub/session/{}/element/active", sess_id),
-            elements_url: format!("wd/hub/session/{}/elements", sess_id),
-            source_url: format!("wd/hub/session/{}/source", sess_id),
-            execute_sync_url: format!("wd/hub/session/{}/execute/sync", sess_id),
-            execute_async_url: format!("wd/hub/session/{}/execute/async", sess_id),
-            cookie_url: format!("wd/hub/session/{}/cookie", sess_id),
-            actions_url: format!("wd/hub/session/{}/actions", sess_id),
-            alert_dismiss_url: format!("wd/hub/session/{}/alert/dismiss", sess_id),
-            alert_accept_url: format!("wd/hub/session/{}/alert/accept", sess_id),
-            alert_text_url: format!("wd/hub/session/{}/alert/text", sess_id),
-            screenshot_url: format!("wd/hub/session/{}/screenshot", sess_id),
-            print_page_url: format!("wd/hub/session/{}/print", sess_id),
-        }
+            transport,
+            go_to_url: format!("{}/url", session_url),
+            timeouts_url: format!("{}/timeouts", session_url),
+            back_url: format!("{}/back", session_url),
+            forward_url: format!("{}/forward", session_url),
+            refresh_url: format!("{}/refresh", session_url),
+            title_url: format!("{}/title", session_url),
+            window_url: format!("{}/window", session_url),
+            window_handles_url: format!("{}/window/handles", session_url),
+            window_new_url: format!("{}/window/new", session_url),
+            window_rect_url: format!("{}/window/rect", session_url),
+            frame_url: format!("{}/frame", session_url),
+            frame_parent_url: format!("{}/frame/parent", session_url),
+            window_maximize_url: format!("{}/window/maximize", session_url),
+            window_minimize_url: format!("{}/window/minimize", session_url),
+            window_fullscreen_url: format!("{}/window/fullscreen", session_url),
+            element_url: format!("{}/element", session_url),
+            element_active_url: format!("{}/element/active", session_url),
+            elements_url: format!("{}/elements", session_url),
+            source_url: format!("{}/source", session_url),
+            execute_sync_url: format!("{}/execute/sync", session_url),
+            execute_async_url: format!("{}/execute/async", session_url),
+            cookie_url: format!("{}/cookie", session_url),
+            actions_url: format!("{}/actions", session_url),
+            alert_dismiss_url: format!("{}/alert/dismiss", session_url),
+            alert_accept_url: format!("{}/alert/accept", session_url),
+            alert_text_url: format!("{}/alert/text", session_url),
+            screenshot_url: format!("{}/screenshot", session_url),
+            print_page_url: format!("{}/print", session_url),
+            session_url,
+            capabilities: SessionCapabilities::default(),
+            driver: None,
+            detached: false,
+        }
+    }
+    pub(crate) fn create_remote_session_body(browser: BrowserName, platform: &str) -> String {
+        let browser = match browser {
+            BrowserName::Chrome => "chrome",
+            BrowserName::Firefox => "firefox",
+            BrowserName::Safari => "safari",
+        };
+        format!(
+            r#"{{
+            "capabilities": {{
+                "alwaysMatch": {{
+                    "platformName": "{}"
+                }},
+                "firstMatch": [
+                    {{"browserName": "{}"}}
+                ]
+            }}
+        }}
+        "#,
+            platform, browser
+        )
     }
-    pub(super) fn create_session_body_json(browser: BrowserName, args: Vec<&str>) -> String {
+    pub(crate) fn create_session_body_json(browser: BrowserName, args: Vec<&str>) -> String {
         match browser {
             BrowserName::Chrome => create_chrome_session(args),
             BrowserName::Firefox => create_firefox_session(),
@@ -1088,7 +929,7 @@ pub(self) mod utils {
         let two = format!(r#"{},"firstMatch":[{{"browserName":"safari"}}]}}}}"#, one);
         two
     }
-    pub(super) fn create_json_body_for_session_with_chrome_options(
+    pub(crate) fn create_json_body_for_session_with_chrome_options(
         chrome_options: ChromeOptions,
     ) -> String {
         let mut options = chrome_options.string_for_session;
@@ -1113,7 +954,7 @@ pub(self) mod utils {
         );
         base_string
     }
-    pub(super) fn create_json_body_for_session_with_firefox_options(
+    pub(crate) fn create_json_body_for_session_with_firefox_options(
         ff_options: FirefoxOptions,
     ) -> String {
         let mut options = ff_options.string_for_session;
@@ -1138,7 +979,7 @@ pub(self) mod utils {
         );
         base_string
     }
-    pub(super) fn create_json_body_for_session_with_safari_options(
+    pub(crate) fn create_json_body_for_session_with_safari_options(
         saf_options: SafariOptions,
     ) -> String {
         let base_string = format!(
@@ -1173,7 +1014,7 @@ pub(self) mod utils {
         result.push_str("]");
         result
     }
-    pub(super) fn gen_script_args(args: &Vec<&str>) -> String {
+    pub(crate) fn gen_script_args(args: &Vec<&str>) -> String {
         if args.len() == 0 {
             return String::from("[]");
         }
@@ -1183,9 +1024,17 @@ pub(self) mod utils {
         result.push_str("]");
         result
     }
-    pub(super) fn from_value_to_cookie(val: &serde_json::Value) -> Cookie {
-        let name = String::from(val["name"].as_str().unwrap());
-        let value = String::from(val["value"].as_str().unwrap());
+    ///Returns the InvalidResponse error if the cookie has no name or value
+    pub(crate) fn from_value_to_cookie(val: &serde_json::Value) -> Result<Cookie, WebDriverError> {
+        let (name, value) = match (val["name"].as_str(), val["value"].as_str()) {
+            (Some(name), Some(value)) => (String::from(name), String::from(value)),
+            _ => {
+                return Err(WebDriverError::InvalidResponse(format!(
+                    "The cookie has no name or value: {}",
+                    val
+                )))
+            }
+        };
         let mut domain = String::from("");
         let mut expiry = 0;
         let mut http_only = false;
@@ -1210,7 +1059,7 @@ pub(self) mod utils {
         if let Some(same) = val["sameSite"].as_str() {
             same_site = String::from(same);
         }
-        Cookie {
+        Ok(Cookie {
             name,
             value,
             path,
@@ -1219,10 +1068,72 @@ pub(self) mod utils {
             domain,
             httpOnly: http_only,
             sameSite: same_site,
-        }
+        })
     }
 }
 
+///The result of the /status command of the server
+///
+/// The Selenium Grid reports its nodes, a standalone server or a driver reports none.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    pub(crate) ready: bool,
+    #[serde(default)]
+    pub(crate) message: String,
+    #[serde(default)]
+    pub(crate) nodes: Vec<NodeStatus>,
+}
+impl ServerStatus {
+    ///Whether the server can create new sessions
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+    pub fn get_nodes(&self) -> &[NodeStatus] {
+        &self.nodes
+    }
+}
+///The state of one Grid node as reported by the /status command
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeStatus {
+    #[serde(default)]
+    pub(crate) id: String,
+    #[serde(default)]
+    pub(crate) uri: String,
+    #[serde(default)]
+    pub(crate) availability: String,
+    #[serde(default)]
+    pub(crate) maxSessions: u32,
+    #[serde(default)]
+    pub(crate) version: String,
+    #[serde(default)]
+    pub(crate) slots: Vec<serde_json::Value>,
+}
+impl NodeStatus {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+    pub fn get_uri(&self) -> &str {
+        &self.uri
+    }
+    ///"UP", "DRAINING" or "DOWN"
+    pub fn get_availability(&self) -> &str {
+        &self.availability
+    }
+    pub fn get_max_sessions(&self) -> u32 {
+        self.maxSessions
+    }
+    pub fn get_version(&self) -> &str {
+        &self.version
+    }
+    ///The raw json of the node slots, including the stereotype capabilities and the running sessions
+    pub fn get_slots(&self) -> &[serde_json::Value] {
+        &self.slots
+    }
+}
 ///Needed to call the new_window method
 pub enum NewWindowType {
     Tab,
@@ -1354,15 +1265,16 @@ impl Default for Cookie {
 }
 ///Utility struct to manage the session's implicit, page load and script timeouts
 #[allow(non_snake_case)]
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Timeouts {
     implicit: u32,
     pageLoad: u32,
-    script: u32,
+    //None (null on the wire) means the scripts never time out
+    script: Option<u64>,
 }
 impl Timeouts {
-    ///Instantiates the Timeouts with all fields set
-    pub fn set_all(implicit: u32, page_load: u32, script: u32) -> Timeouts {
+    ///Instantiates the Timeouts with all fields set, the None script timeout disables it
+    pub fn set_all(implicit: u32, page_load: u32, script: Option<u64>) -> Timeouts {
         Timeouts {
             implicit,
             pageLoad: page_load,
@@ -1374,16 +1286,26 @@ impl Timeouts {
         Timeouts {
             implicit: 0,
             pageLoad: 300000,
-            script: 30000,
+            script: Some(30000),
         }
     }
+    pub fn get_implicit(&self) -> u32 {
+        self.implicit
+    }
+    pub fn get_page_load(&self) -> u32 {
+        self.pageLoad
+    }
+    ///None if the scripts never time out
+    pub fn get_script(&self) -> Option<u64> {
+        self.script
+    }
     pub fn set_implicit(&mut self, implicit: u32) {
         self.implicit = implicit;
     }
     pub fn set_page_load(&mut self, page_load: u32) {
         self.pageLoad = page_load;
     }
-    pub fn set_script(&mut self, script: u32) {
+    pub fn set_script(&mut self, script: Option<u64>) {
         self.script = script;
     }
 }
@@ -1611,13 +1533,13 @@ mod core_fns_tests {
             timeouts = br.get_timeouts().unwrap();
             br.close_browser().unwrap();
         }
-        assert!(timeouts.implicit == 0 && timeouts.script == 30000);
+        assert!(timeouts.implicit == 0 && timeouts.script == Some(30000));
     }
     #[test]
     fn set_timeouts() {
         let mut br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
-        let timeouts = Timeouts::set_all(1000, 3000, 300000);
-        assert!(br.set_timeouts(&timeouts) == Ok(()));
+        let timeouts = Timeouts::set_all(1000, 3000, Some(300000));
+        assert!(br.set_timeouts(&timeouts).is_ok());
         br.close_browser().unwrap();
     }
     #[test]
@@ -1628,7 +1550,7 @@ mod core_fns_tests {
             Timeouts {
                 implicit: 0,
                 pageLoad: 300000,
-                script: 30000
+                script: Some(30000)
             }
         );
         t.set_implicit(1);
@@ -1637,7 +1559,7 @@ mod core_fns_tests {
             Timeouts {
                 implicit: 1,
                 pageLoad: 300000,
-                script: 30000
+                script: Some(30000)
             }
         );
         t.set_page_load(1);
@@ -1646,16 +1568,16 @@ mod core_fns_tests {
             Timeouts {
                 implicit: 1,
                 pageLoad: 1,
-                script: 30000
+                script: Some(30000)
             }
         );
-        t.set_script(1);
+        t.set_script(Some(1));
         assert_eq!(
             t,
             Timeouts {
                 implicit: 1,
                 pageLoad: 1,
-                script: 1
+                script: Some(1)
             }
         );
     }
@@ -1690,7 +1612,7 @@ mod core_fns_tests {
     fn refresh_test() {
         let mut br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
         br.open("https://vk.com/").unwrap();
-        assert_eq!(br.refresh(), Ok(()));
+        assert!(br.refresh().is_ok());
         br.close_browser().unwrap();
     }
     #[test]
@@ -1717,12 +1639,12 @@ mod core_fns_tests {
     }
     #[test]
     fn switch_window() {
-        let res: Result<(), String>;
+        let res: Result<(), WebDriverError>;
         let mut br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
         br.open("https://vk.com").unwrap();
         let handle = br.get_window_handle().unwrap();
         res = br.switch_to_window(handle);
-        assert_eq!(Ok(()), res);
+        assert!(res.is_ok());
         br.close_browser().unwrap();
     }
     #[test]
@@ -1761,7 +1683,7 @@ mod core_fns_tests {
         br.open("https://bash.im").unwrap();
         let res = br.switch_to_frame_by_id(0);
         br.close_browser().unwrap();
-        assert_eq!(res, Ok(()));
+        assert!(res.is_ok());
     }
     #[test]
     fn sw_t_fr_by_el() {
@@ -1772,7 +1694,7 @@ mod core_fns_tests {
             .unwrap();
         let res = br.switch_to_frame_by_element(el);
         br.close_browser().unwrap();
-        assert_eq!(res, Ok(()));
+        assert!(res.is_ok());
     }
     #[test]
     fn find_element() {
@@ -1805,7 +1727,7 @@ mod core_fns_tests {
         br.open("https://vk.com").unwrap();
         res = br.switch_to_parent_frame();
         br.close_browser().unwrap();
-        assert_eq!(res, Ok(()));
+        assert!(res.is_ok());
     }
     #[test]
     fn get_wind_rect() {
@@ -1857,19 +1779,6 @@ mod core_fns_tests {
         assert_eq!(a, wr);
     }
     #[test]
-    fn parse_val_test() {
-        let resp = r#"{
-       "value": {
-       "dftg43rert34tert-34trte-243f-4":
-       {
-        "id": 333
-        }
-      }
-     }"#;
-        let res = parse_value(resp);
-        assert_eq!(res, "{\"dftg43rert34tert-34trte-243f-4\":{\"id\":333}}");
-    }
-    #[test]
     fn fullsize() {
         let mut br = Browser::start_session(
             BrowserName::Chrome,
@@ -1934,7 +1843,7 @@ mod core_fns_tests {
             false,
             String::from("0%7C1604223632022"),
         );
-        assert_eq!(br.add_cookie(cook), Ok(()));
+        assert!(br.add_cookie(cook).is_ok());
         br.close_browser().unwrap();
     }
 
@@ -1953,7 +1862,7 @@ mod core_fns_tests {
         br.open("https://vk.com").unwrap();
         let r = br.delete_cookie("remixjsp");
         br.close_browser().unwrap();
-        assert!(r == Ok(()));
+        assert!(r.is_ok());
     }
     #[test]
     fn screensh() {
@@ -2198,7 +2107,7 @@ mod core_fns_tests {
             .unwrap();
         let res = el.click();
         br.close_browser().unwrap();
-        assert_eq!(res, Ok(()))
+        assert!(res.is_ok())
     }
     #[test]
     fn el_send_k() {
@@ -2479,3 +2388,267 @@ mod safari_tests {
         br.close_browser().unwrap();
     }
 }
+mod mock_tests {
+    use super::*;
+    #[test]
+    fn observer_sees_new_session() {
+        #[derive(Debug)]
+        struct Recorder(Arc<std::sync::Mutex<Vec<String>>>);
+        impl WireObserver for Recorder {
+            fn on_request(&self, method: Method, path: &str, _body: &str) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{:?} {}", method, path));
+            }
+        }
+        let seen = Arc::new(std::sync::Mutex::new(vec![]));
+        let mock = MockTransport::new();
+        mock.expect(
+            Method::POST,
+            "wd/hub/session",
+            r#"{"value":{"sessionId":"s1","capabilities":{}}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/title",
+            r#"{"value":"Home"}"#,
+        );
+        let mut c = Capabilities::new(BrowserName::Chrome, "linux");
+        c.set_wire_observer(Recorder(seen.clone()));
+        let mut br = Browser::start_session_with_transport(mock.clone(), c).unwrap();
+        br.detach();
+        br.get_title().unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["POST wd/hub/session", "GET wd/hub/session/s1/title"]
+        );
+    }
+    #[test]
+    fn commands_follow_the_url_prefix() {
+        let mock = MockTransport::new();
+        mock.expect(
+            Method::POST,
+            "session",
+            r#"{"value":{"sessionId":"s2","capabilities":{}}}"#,
+        )
+        .expect(Method::GET, "session/s2/title", r#"{"value":"Root"}"#);
+        let url = ServerUrl::parse("http://localhost:9515").unwrap();
+        let body = create_session_body_json(BrowserName::Chrome, vec![]);
+        let br = new_session(Arc::new(mock.clone()), &url, &body, None).unwrap();
+        assert_eq!(br.get_title().unwrap(), "Root");
+        assert!(mock.is_done());
+    }
+    #[test]
+    fn grid_status() {
+        let mock = MockTransport::new();
+        mock.expect(
+            Method::GET,
+            "status",
+            r#"{"value":{"ready":true,"message":"Selenium Grid ready.","nodes":[
+            {"id":"n1","uri":"http://10.0.0.2:5555","maxSessions":2,"availability":"UP",
+            "version":"4.1.0","slots":[{}]}]}}"#,
+        );
+        let url = ServerUrl::parse("http://localhost:4444").unwrap();
+        let status = server_status_with(&mock, &url).unwrap();
+        assert!(status.is_ready());
+        assert_eq!(status.get_nodes()[0].get_max_sessions(), 2);
+        assert_eq!(status.get_nodes()[0].get_slots().len(), 1);
+    }
+    #[test]
+    fn drop_deletes_session() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(Method::DELETE, "wd/hub/session/s1", r#"{"value":null}"#);
+        drop(br);
+        assert!(mock.is_done());
+    }
+    #[test]
+    fn closed_session_sends_nothing() {
+        let (mock, mut br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#,
+        )
+        .expect(Method::DELETE, "wd/hub/session/s1", r#"{"value":null}"#);
+        let el = br.find_element(LocatorStrategy::CSS("#a")).unwrap();
+        br.close_browser().unwrap();
+        assert!(matches!(br.get_title(), Err(WebDriverError::SessionClosed)));
+        assert!(matches!(el.click(), Err(WebDriverError::SessionClosed)));
+        drop(br);
+        assert_eq!(mock.requests().len(), 3);
+    }
+    #[test]
+    fn detached_session_stays_open() {
+        let (mock, mut br) = mock_browser("chrome");
+        br.detach();
+        drop(br);
+        assert_eq!(mock.requests().len(), 1);
+    }
+    #[test]
+    fn find_and_read_text() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/e1/text",
+            r#"{"value":"Hello"}"#,
+        );
+        let el = br.find_element(LocatorStrategy::CSS("#greeting")).unwrap();
+        assert_eq!(el.get_element_text().unwrap(), "Hello");
+        assert!(mock.is_done());
+        let find_req = &mock.requests()[1];
+        assert_eq!(
+            find_req.get_body(),
+            r##"{"using":"css selector","value":"#greeting"}"##
+        );
+    }
+    #[test]
+    fn stale_element_is_found_again() {
+        let (mock, br) = mock_browser("chrome");
+        let stale = r#"{"value":{"error":"stale element reference","message":"","stacktrace":""}}"#;
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"form1"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/form1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"btn1"}}"#,
+        )
+        .expect_status(
+            Method::GET,
+            "wd/hub/session/s1/element/btn1/text",
+            404,
+            stale,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"form2"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/form2/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"btn2"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/btn2/text",
+            r#"{"value":"Send"}"#,
+        )
+        .expect_status(
+            Method::GET,
+            "wd/hub/session/s1/element/btn2/text",
+            404,
+            stale,
+        );
+        let form = br.find_element(LocatorStrategy::CSS("form")).unwrap();
+        let mut button = form
+            .find_element_from_self(LocatorStrategy::CSS("button"))
+            .unwrap();
+        assert_eq!(button.get_element_text().unwrap(), "Send");
+        assert_eq!(
+            mock.requests()[5].get_body(),
+            r#"{"using":"css selector","value":"button"}"#
+        );
+        button.set_auto_refresh(false);
+        let res = button.get_element_text();
+        assert!(matches!(res, Err(WebDriverError::StaleElementReference(_))));
+        assert!(mock.is_done());
+    }
+    #[test]
+    fn server_errors_are_typed() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect_status(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            404,
+            r#"{"value":{"error":"no such element","message":"Unable to locate","stacktrace":""}}"#,
+        );
+        let res = br.find_element(LocatorStrategy::CSS("#missing"));
+        assert!(matches!(res, Err(WebDriverError::NoSuchElement(_))));
+    }
+    #[test]
+    fn open_escapes_url() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(Method::POST, "wd/hub/session/s1/url", r#"{"value":null}"#);
+        br.open(r#"https://example.com/?q="a""#).unwrap();
+        let body: serde_json::Value = serde_json::from_str(mock.requests()[1].get_body()).unwrap();
+        assert_eq!(body["url"], r#"https://example.com/?q="a""#);
+    }
+    #[test]
+    fn malformed_cookie_is_an_error() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::GET,
+            "wd/hub/session/s1/cookie",
+            r#"{"value":[{"name":"sid","value":"1"},{"name":"partial"}]}"#,
+        );
+        let res = br.get_all_cookies();
+        assert!(matches!(res, Err(WebDriverError::InvalidResponse(_))));
+    }
+    #[test]
+    fn native_full_page_screenshots() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/goog/cdp/execute",
+            r#"{"value":{"cssContentSize":{"x":0,"y":0,"width":800,"height":3000.5}}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/goog/cdp/execute",
+            r#"{"value":{"data":"cG5n"}}"#,
+        );
+        assert_eq!(br.get_full_page_screenshot_png().unwrap(), b"png");
+        let body: serde_json::Value = serde_json::from_str(mock.requests()[2].get_body()).unwrap();
+        assert_eq!(body["cmd"], "Page.captureScreenshot");
+        assert_eq!(body["params"]["captureBeyondViewport"], true);
+        assert_eq!(body["params"]["clip"]["height"], 3000.5);
+
+        let (mock, br) = mock_browser("firefox");
+        mock.expect(
+            Method::GET,
+            "wd/hub/session/s1/moz/screenshot/full",
+            r#"{"value":"ZnVsbA=="}"#,
+        );
+        assert_eq!(br.get_full_page_screenshot_png().unwrap(), b"full");
+        assert!(mock.is_done());
+
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/goog/cdp/execute",
+            r#"{"value":{"contentSize":{"x":0,"y":0,"width":640,"height":2000}}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/goog/cdp/execute",
+            r#"{"value":{"data":"cG5n"}}"#,
+        );
+        assert_eq!(br.get_full_page_screenshot_png().unwrap(), b"png");
+        let body: serde_json::Value = serde_json::from_str(mock.requests()[2].get_body()).unwrap();
+        assert_eq!(body["params"]["clip"]["width"], 640);
+        assert_eq!(body["params"]["clip"]["height"], 2000);
+
+        let (mock, br) = mock_browser("msedge");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/ms/cdp/execute",
+            r#"{"value":{"cssContentSize":{"x":0,"y":0,"width":800,"height":600}}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/ms/cdp/execute",
+            r#"{"value":{"data":"ZWRnZQ=="}}"#,
+        );
+        assert_eq!(br.get_full_page_screenshot_png().unwrap(), b"edge");
+        assert!(mock.is_done());
+    }
+}
@@ -0,0 +1,153 @@
+use super::error::*;
+use super::reqs::*;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+type StrategyFn = Arc<dyn Fn(&str) -> LocatorStrategy<String> + Send + Sync>;
+
+fn registry() -> &'static RwLock<HashMap<String, StrategyFn>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, StrategyFn>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+///Shortcuts for the common lookups which are translated to the CSS selectors
+///
+/// The values are escaped, so any id or attribute value may be passed as is.
+/// Teams may also register their own named strategies with By::register and use them with By::custom.
+///
+/// # Examples
+/// ```
+/// # use selenium_webdriver::*;
+/// assert_eq!(By::id("main").get_value(), "#main");
+/// assert_eq!(By::id("1st").get_value(), r"#\31 st");
+/// assert_eq!(By::test_id("login \"button\"").get_value(), r#"[data-testid="login \"button\""]"#);
+///
+/// By::register("qa", |v| LocatorStrategy::CSS(format!("[data-qa={}]", By::css_string(v))));
+/// let loc = By::custom("qa", "submit").unwrap();
+/// assert_eq!(loc.get_value(), r#"[data-qa="submit"]"#);
+/// ```
+pub struct By;
+impl By {
+    ///The element with the id attribute
+    pub fn id(id: &str) -> LocatorStrategy<String> {
+        LocatorStrategy::CSS(format!("#{}", By::css_escape(id)))
+    }
+    ///The elements with the name attribute, e.g. form fields
+    pub fn name(name: &str) -> LocatorStrategy<String> {
+        LocatorStrategy::CSS(format!("[name={}]", By::css_string(name)))
+    }
+    ///The elements having the class. Whitespace is not allowed in a class name,
+    /// use CSS with several classes (".a.b") to match compound ones.
+    pub fn class_name(class_name: &str) -> LocatorStrategy<String> {
+        LocatorStrategy::CSS(format!(".{}", By::css_escape(class_name)))
+    }
+    ///The elements with the data-testid attribute
+    pub fn test_id(test_id: &str) -> LocatorStrategy<String> {
+        LocatorStrategy::CSS(format!("[data-testid={}]", By::css_string(test_id)))
+    }
+    ///Registers the named strategy for By::custom, replacing the one with the same name if any
+    pub fn register<F>(name: &str, strategy: F)
+    where
+        F: Fn(&str) -> LocatorStrategy<String> + Send + Sync + 'static,
+    {
+        registry()
+            .write()
+            .unwrap()
+            .insert(String::from(name), Arc::new(strategy));
+    }
+    ///Builds the locator with the registered strategy. Returns the InvalidSelector error
+    /// if there is no strategy with this name.
+    pub fn custom(name: &str, value: &str) -> Result<LocatorStrategy<String>, WebDriverError> {
+        let strategy = registry().read().unwrap().get(name).cloned();
+        match strategy {
+            Some(strategy) => Ok(strategy(value)),
+            None => Err(WebDriverError::InvalidSelector(ErrorDetails {
+                error: String::from("invalid selector"),
+                message: format!("The locator strategy \"{}\" is not registered", name),
+                stacktrace: String::new(),
+                data: None,
+            })),
+        }
+    }
+    ///Escapes the CSS identifier (e.g. an id or a class name) like CSS.escape() does in the browser
+    pub fn css_escape(ident: &str) -> String {
+        let chars: Vec<char> = ident.chars().collect();
+        let mut result = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let starts_with_digit = c.is_ascii_digit() && (i == 0 || (i == 1 && chars[0] == '-'));
+            if c == '\0' {
+                result.push('\u{FFFD}');
+            } else if (c.is_control() && (c as u32) < 0x80) || starts_with_digit {
+                result.push_str(&format!("\\{:x} ", c as u32));
+            } else if i == 0 && c == '-' && chars.len() == 1 {
+                result.push_str("\\-");
+            } else if (c as u32) >= 0x80 || c == '-' || c == '_' || c.is_ascii_alphanumeric() {
+                result.push(c);
+            } else {
+                result.push('\\');
+                result.push(c);
+            }
+        }
+        result
+    }
+    ///Quotes the value as a CSS string, e.g. for an attribute selector
+    pub fn css_string(value: &str) -> String {
+        let mut result = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '"' | '\\' => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                '\0' => result.push('\u{FFFD}'),
+                c if c.is_control() => result.push_str(&format!("\\{:x} ", c as u32)),
+                c => result.push(c),
+            }
+        }
+        result.push('"');
+        result
+    }
+    ///Quotes the value as an XPath string literal, using concat() if it has both kinds of quotes
+    pub fn xpath_literal(value: &str) -> String {
+        if !value.contains('\'') {
+            return format!("'{}'", value);
+        }
+        if !value.contains('"') {
+            return format!("\"{}\"", value);
+        }
+        let parts: Vec<String> = value.split('\'').map(|p| format!("'{}'", p)).collect();
+        format!("concat({})", parts.join(r#", "'", "#))
+    }
+}
+
+mod by_tests {
+    use super::*;
+    #[test]
+    fn css_identifiers() {
+        assert_eq!(By::css_escape("a.b:c"), r"a\.b\:c");
+        assert_eq!(By::css_escape("-1x"), r"-\31 x");
+        assert_eq!(By::css_escape("-"), r"\-");
+        assert_eq!(By::css_escape("tab\there"), r"tab\9 here");
+        assert_eq!(By::css_escape("ключ_1"), "ключ_1");
+        assert_eq!(By::class_name("btn primary").get_value(), r".btn\ primary");
+    }
+    #[test]
+    fn css_and_xpath_strings() {
+        assert_eq!(By::name(r"a\b").get_value(), r#"[name="a\\b"]"#);
+        assert_eq!(By::xpath_literal("it's"), r#""it's""#);
+        assert_eq!(By::xpath_literal(r#"a'b"c"#), r#"concat('a', "'", 'b"c')"#);
+    }
+    #[test]
+    fn custom_strategies() {
+        assert!(matches!(
+            By::custom("not registered", "x"),
+            Err(WebDriverError::InvalidSelector(_))
+        ));
+        By::register("label", |v| {
+            LocatorStrategy::XPATH(format!("//*[@aria-label={}]", By::xpath_literal(v)))
+        });
+        let loc = By::custom("label", "Close").unwrap();
+        assert_eq!(loc.get_using(), "xpath");
+        assert_eq!(loc.get_value(), "//*[@aria-label='Close']");
+    }
+}
@@ -1,8 +1,12 @@
 use super::ChromeOptions;
 use super::FirefoxOptions;
 use super::SafariOptions;
+use super::WireObserver;
 use super::{BrowserName, Timeouts};
 use super::{Proxy, ProxyType};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
 ///Utility struct to set up the session
 ///
 /// The capabilities will always match, meaning that even if a single requirement cannot
@@ -13,6 +17,7 @@ use super::{Proxy, ProxyType};
 /// For more info please visit https://www.w3.org/TR/webdriver/#capabilities
 pub struct Capabilities {
     pub(crate) cap_string: String,
+    pub(crate) observer: Option<Arc<dyn WireObserver>>,
 }
 
 impl Capabilities {
@@ -31,7 +36,10 @@ impl Capabilities {
                     "platformName": "{}"}}}}}}"#,
             browser, platform
         );
-        Capabilities { cap_string }
+        Capabilities {
+            cap_string,
+            observer: None,
+        }
     }
     pub fn set_chrome_options(&mut self, options: ChromeOptions) -> &mut Self {
         let text = options.string_for_session;
@@ -97,12 +105,160 @@ impl Capabilities {
         update(&mut self.cap_string, text);
         self
     }
+    ///Sets the wire observer of the session, e.g. the FileWireLogger. Unlike Browser::set_wire_observer,
+    /// the observer is also notified about the new session request.
+    pub fn set_wire_observer<O: WireObserver + 'static>(&mut self, observer: O) -> &mut Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
     pub fn set_unhandled_prompt_behavior(&mut self, behavior: &str) -> &mut Self {
         let text = format!(r#""unhandledPromptBehavior":"{}""#, behavior);
         update(&mut self.cap_string, &text);
         self
     }
 }
+///The capabilities the server actually granted to the session, as returned by the new session command
+///
+/// The standard capabilities are typed, the vendor specific ones (e.g. "goog:chromeOptions")
+/// can be read as json with get_extension().
+///
+/// # Examples
+/// ```
+/// # use selenium_webdriver::*;
+/// let mock = MockTransport::new();
+/// mock.expect(Method::POST, "wd/hub/session", r#"{"value":{"sessionId":"1","capabilities":{
+///     "browserName":"chrome","browserVersion":"96.0","platformName":"linux",
+///     "goog:chromeOptions":{"debuggerAddress":"localhost:41423"}}}}"#);
+/// let c = Capabilities::new(BrowserName::Chrome, "linux");
+/// let mut br = Browser::start_session_with_transport(mock.clone(), c).unwrap();
+/// assert_eq!(br.capabilities().get_browser_version(), "96.0");
+/// assert_eq!(br.capabilities().get_debugger_address(), Some("localhost:41423"));
+/// # br.detach();
+/// ```
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCapabilities {
+    #[serde(default)]
+    pub(crate) browser_name: String,
+    #[serde(default)]
+    pub(crate) browser_version: String,
+    #[serde(default)]
+    pub(crate) platform_name: String,
+    #[serde(default)]
+    pub(crate) accept_insecure_certs: bool,
+    #[serde(default)]
+    pub(crate) page_load_strategy: String,
+    #[serde(default)]
+    pub(crate) set_window_rect: bool,
+    #[serde(default)]
+    pub(crate) strict_file_interactability: bool,
+    #[serde(default)]
+    pub(crate) unhandled_prompt_behavior: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub(crate) timeouts: Option<Timeouts>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub(crate) proxy: Option<SessionProxy>,
+    #[serde(flatten)]
+    pub(crate) extensions: HashMap<String, serde_json::Value>,
+}
+impl SessionCapabilities {
+    pub fn get_browser_name(&self) -> &str {
+        &self.browser_name
+    }
+    pub fn get_browser_version(&self) -> &str {
+        &self.browser_version
+    }
+    pub fn get_platform_name(&self) -> &str {
+        &self.platform_name
+    }
+    pub fn get_accept_insecure_certs(&self) -> bool {
+        self.accept_insecure_certs
+    }
+    pub fn get_page_load_strategy(&self) -> &str {
+        &self.page_load_strategy
+    }
+    ///Whether the window can be resized and moved with set_window_rect
+    pub fn get_set_window_rect(&self) -> bool {
+        self.set_window_rect
+    }
+    pub fn get_strict_file_interactability(&self) -> bool {
+        self.strict_file_interactability
+    }
+    pub fn get_unhandled_prompt_behavior(&self) -> &str {
+        &self.unhandled_prompt_behavior
+    }
+    ///None if the server did not report the timeouts or reported the script timeout as null
+    pub fn get_timeouts(&self) -> Option<&Timeouts> {
+        self.timeouts.as_ref()
+    }
+    pub fn get_proxy(&self) -> Option<&SessionProxy> {
+        self.proxy.as_ref()
+    }
+    ///Any capability which is not typed above, e.g. "goog:chromeOptions" or "moz:profile"
+    pub fn get_extension(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extensions.get(name)
+    }
+    ///The address of the DevTools endpoint of Chrome or Edge, e.g. "localhost:41423"
+    pub fn get_debugger_address(&self) -> Option<&str> {
+        ["goog:chromeOptions", "ms:edgeOptions"]
+            .iter()
+            .filter_map(|name| self.get_extension(name))
+            .find_map(|options| options["debuggerAddress"].as_str())
+    }
+}
+///The proxy settings of the session, see https://www.w3.org/TR/webdriver/#proxy
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionProxy {
+    #[serde(default)]
+    pub(crate) proxy_type: String,
+    pub(crate) proxy_autoconfig_url: Option<String>,
+    pub(crate) ftp_proxy: Option<String>,
+    pub(crate) http_proxy: Option<String>,
+    #[serde(default)]
+    pub(crate) no_proxy: Vec<String>,
+    pub(crate) ssl_proxy: Option<String>,
+    pub(crate) socks_proxy: Option<String>,
+    pub(crate) socks_version: Option<u8>,
+}
+impl SessionProxy {
+    ///Empty if the server did not report the proxy type
+    pub fn get_proxy_type(&self) -> &str {
+        &self.proxy_type
+    }
+    pub fn get_proxy_autoconfig_url(&self) -> Option<&str> {
+        self.proxy_autoconfig_url.as_deref()
+    }
+    pub fn get_ftp_proxy(&self) -> Option<&str> {
+        self.ftp_proxy.as_deref()
+    }
+    pub fn get_http_proxy(&self) -> Option<&str> {
+        self.http_proxy.as_deref()
+    }
+    pub fn get_no_proxy(&self) -> &[String] {
+        &self.no_proxy
+    }
+    pub fn get_ssl_proxy(&self) -> Option<&str> {
+        self.ssl_proxy.as_deref()
+    }
+    pub fn get_socks_proxy(&self) -> Option<&str> {
+        self.socks_proxy.as_deref()
+    }
+    pub fn get_socks_version(&self) -> Option<u8> {
+        self.socks_version
+    }
+}
+//The drivers are not consistent in what they return,
+//so a value that does not fit the type is skipped rather than failing the whole session
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
 fn update(st: &mut String, text: &str) {
     st.pop();
     st.pop();
@@ -156,6 +312,30 @@ mod capab_tests {
         println!("{}", c.cap_string);
     }
     #[test]
+    fn negotiated_capabilities() {
+        let caps: SessionCapabilities = serde_json::from_str(
+            r#"{"browserName":"firefox","browserVersion":"95.0","platformName":"linux",
+            "setWindowRect":true,"timeouts":{"implicit":0,"pageLoad":300000,"script":null},
+            "proxy":{"proxyType":"manual","httpProxy":"proxy:3128","noProxy":["localhost"]},
+            "moz:profile":"/tmp/rust_mozprofile"}"#,
+        )
+        .unwrap();
+        assert_eq!(caps.get_browser_name(), "firefox");
+        assert!(caps.get_set_window_rect());
+        let timeouts = caps.get_timeouts().unwrap();
+        assert_eq!(timeouts.get_implicit(), 0);
+        assert_eq!(timeouts.get_page_load(), 300000);
+        assert_eq!(timeouts.get_script(), None);
+        let proxy = caps.get_proxy().unwrap();
+        assert_eq!(proxy.get_http_proxy(), Some("proxy:3128"));
+        assert_eq!(proxy.get_no_proxy(), ["localhost"]);
+        assert_eq!(
+            caps.get_extension("moz:profile").unwrap(),
+            "/tmp/rust_mozprofile"
+        );
+        assert_eq!(caps.get_debugger_address(), None);
+    }
+    #[test]
     fn cap_all() {
         let mut cap = Capabilities::new(BrowserName::Firefox, "windows");
         let t = Timeouts::new();
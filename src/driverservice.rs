@@ -0,0 +1,292 @@
+use super::browser::*;
+use super::error::*;
+use super::reqs::*;
+use super::serverurl::*;
+use super::transport::*;
+use std::fs::File;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+///The verbosity of the driver log, mapped to the flags of each driver
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DriverLogLevel {
+    Off,
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+///Launches chromedriver, geckodriver or safaridriver locally, so no selenium server is needed
+///
+/// The driver is looked up on PATH unless the executable is set explicitly. If the port is not set,
+/// a free one is picked. The process is killed when the Browser started with the service is closed or dropped.
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// let mut service = DriverService::new(BrowserName::Chrome);
+/// service.set_log_file("chromedriver.log").set_log_level(DriverLogLevel::Debug);
+/// let c = Capabilities::new(BrowserName::Chrome, "linux");
+/// let mut br = Browser::start_session_with_service(&service, c).unwrap();
+/// br.open("https://vk.com").unwrap();
+/// br.close_browser().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DriverService {
+    browser: BrowserName,
+    executable: Option<PathBuf>,
+    port: Option<u16>,
+    log_file: Option<PathBuf>,
+    log_level: Option<DriverLogLevel>,
+    args: Vec<String>,
+    startup_timeout: Duration,
+}
+impl DriverService {
+    pub fn new(browser: BrowserName) -> Self {
+        DriverService {
+            browser,
+            executable: None,
+            port: None,
+            log_file: None,
+            log_level: None,
+            args: vec![],
+            startup_timeout: Duration::from_secs(20),
+        }
+    }
+    ///Path to the driver executable, if it is not on PATH
+    pub fn set_executable<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.executable = Some(path.as_ref().to_path_buf());
+        self
+    }
+    pub fn set_port(&mut self, port: u16) -> &mut Self {
+        self.port = Some(port);
+        self
+    }
+    ///The stdout and stderr of the driver are written to this file, otherwise they are discarded
+    pub fn set_log_file<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.log_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+    ///Safaridriver has no levels, any level but Off turns on its --diagnose mode
+    pub fn set_log_level(&mut self, level: DriverLogLevel) -> &mut Self {
+        self.log_level = Some(level);
+        self
+    }
+    ///Additional command line arguments of the driver
+    pub fn add_args(&mut self, args: Vec<&str>) -> &mut Self {
+        self.args.extend(args.iter().map(|a| String::from(*a)));
+        self
+    }
+    ///How long to wait for the driver to report it is ready. 20 seconds by default.
+    pub fn set_startup_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.startup_timeout = timeout;
+        self
+    }
+    ///Spawns the driver and waits until its /status endpoint reports it is ready
+    ///
+    /// The returned DriverProcess kills the driver when dropped. The Browser::start_session_with_service
+    /// keeps it inside the Browser, but it can also be used directly, e.g. with the AsyncBrowser.
+    pub fn start(&self) -> Result<DriverProcess, WebDriverError> {
+        let executable = match &self.executable {
+            Some(path) => path.clone(),
+            None => find_on_path(self.executable_name())?,
+        };
+        let port = match self.port {
+            Some(port) => port,
+            None => free_port()?,
+        };
+        let mut command = Command::new(&executable);
+        command.args(self.driver_args(port)).stdin(Stdio::null());
+        match &self.log_file {
+            Some(path) => {
+                let log = File::create(path).map_err(WebDriverError::Io)?;
+                let log_err = log.try_clone().map_err(WebDriverError::Io)?;
+                command.stdout(log).stderr(log_err);
+            }
+            None => {
+                command.stdout(Stdio::null()).stderr(Stdio::null());
+            }
+        }
+        let child = command.spawn().map_err(|e| {
+            WebDriverError::Driver(format!("could not start {}: {}", executable.display(), e))
+        })?;
+        let mut url = ServerUrl::new("127.0.0.1", port);
+        url.set_prefix("");
+        let mut process = DriverProcess { child, url };
+        process.wait_until_ready(self.startup_timeout)?;
+        Ok(process)
+    }
+    fn executable_name(&self) -> &'static str {
+        match self.browser {
+            BrowserName::Chrome => "chromedriver",
+            BrowserName::Firefox => "geckodriver",
+            BrowserName::Safari => "safaridriver",
+        }
+    }
+    fn driver_args(&self, port: u16) -> Vec<String> {
+        let mut args = match self.browser {
+            BrowserName::Chrome => vec![format!("--port={}", port)],
+            _ => vec![String::from("--port"), port.to_string()],
+        };
+        if let Some(level) = self.log_level {
+            match self.browser {
+                BrowserName::Chrome => {
+                    let level = match level {
+                        DriverLogLevel::Off => "OFF",
+                        DriverLogLevel::Error => "SEVERE",
+                        DriverLogLevel::Warning => "WARNING",
+                        DriverLogLevel::Info => "INFO",
+                        DriverLogLevel::Debug => "DEBUG",
+                        DriverLogLevel::Trace => "ALL",
+                    };
+                    args.push(format!("--log-level={}", level));
+                }
+                BrowserName::Firefox => {
+                    let level = match level {
+                        DriverLogLevel::Off => "fatal",
+                        DriverLogLevel::Error => "error",
+                        DriverLogLevel::Warning => "warn",
+                        DriverLogLevel::Info => "info",
+                        DriverLogLevel::Debug => "debug",
+                        DriverLogLevel::Trace => "trace",
+                    };
+                    args.push(String::from("--log"));
+                    args.push(String::from(level));
+                }
+                BrowserName::Safari => {
+                    if level != DriverLogLevel::Off {
+                        args.push(String::from("--diagnose"));
+                    }
+                }
+            }
+        }
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+///The running driver started by the DriverService, killed when dropped
+#[derive(Debug)]
+pub struct DriverProcess {
+    child: Child,
+    url: ServerUrl,
+}
+impl DriverProcess {
+    ///The url the driver listens on, to be passed to Browser::start_session_with_url
+    pub fn get_url(&self) -> &ServerUrl {
+        &self.url
+    }
+    pub fn get_pid(&self) -> u32 {
+        self.child.id()
+    }
+    fn wait_until_ready(&mut self, timeout: Duration) -> Result<(), WebDriverError> {
+        let port = self.url.get_port().to_string();
+        let started = Instant::now();
+        loop {
+            if let Some(status) = self.child.try_wait().map_err(WebDriverError::Io)? {
+                return Err(WebDriverError::Driver(format!(
+                    "the driver exited during the startup with {}",
+                    status
+                )));
+            }
+            //A driver which accepts the connection but never answers must not outlive the timeout
+            let remaining = timeout.saturating_sub(started.elapsed());
+            let mut transport = TcpTransport::new("127.0.0.1", &port);
+            transport.set_read_timeout(Some(remaining.max(Duration::from_millis(10))));
+            if let Ok(value) = send_command(&transport, Method::GET, "status", "") {
+                if value["ready"].as_bool().unwrap_or(false) {
+                    return Ok(());
+                }
+            }
+            if started.elapsed() > timeout {
+                return Err(WebDriverError::Driver(format!(
+                    "the driver was not ready after {:?}",
+                    timeout
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+impl Drop for DriverProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn find_on_path(name: &str) -> Result<PathBuf, WebDriverError> {
+    let file_name = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+    std::env::var_os("PATH")
+        .and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(&file_name))
+                .find(|path| path.is_file())
+        })
+        .ok_or_else(|| WebDriverError::Driver(format!("{} was not found on PATH", file_name)))
+}
+fn free_port() -> Result<u16, WebDriverError> {
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(WebDriverError::Io)?;
+    Ok(listener.local_addr().map_err(WebDriverError::Io)?.port())
+}
+
+mod driver_service_tests {
+    use super::*;
+    #[test]
+    fn chrome_args() {
+        let mut service = DriverService::new(BrowserName::Chrome);
+        service
+            .set_log_level(DriverLogLevel::Trace)
+            .add_args(vec!["--allowed-ips="]);
+        assert_eq!(
+            service.driver_args(9515),
+            vec!["--port=9515", "--log-level=ALL", "--allowed-ips="]
+        );
+    }
+    #[test]
+    fn gecko_and_safari_args() {
+        let mut gecko = DriverService::new(BrowserName::Firefox);
+        gecko.set_log_level(DriverLogLevel::Warning);
+        assert_eq!(gecko.driver_args(1), vec!["--port", "1", "--log", "warn"]);
+        let mut safari = DriverService::new(BrowserName::Safari);
+        safari.set_log_level(DriverLogLevel::Off);
+        assert_eq!(safari.driver_args(2), vec!["--port", "2"]);
+    }
+    #[test]
+    fn missing_executable() {
+        let mut service = DriverService::new(BrowserName::Chrome);
+        service.set_executable("/definitely/not/chromedriver");
+        assert!(matches!(service.start(), Err(WebDriverError::Driver(_))));
+    }
+    #[test]
+    #[cfg(unix)]
+    fn exited_driver_is_reported() {
+        let mut service = DriverService::new(BrowserName::Firefox);
+        service
+            .set_executable("/bin/false")
+            .set_startup_timeout(Duration::from_secs(5));
+        let err = service.start().unwrap_err();
+        assert!(err.to_string().contains("exited"));
+    }
+    #[test]
+    #[cfg(unix)]
+    fn silent_driver_times_out() {
+        //The kernel accepts the connections, but nobody answers them
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut process = DriverProcess {
+            child: Command::new("sleep").arg("30").spawn().unwrap(),
+            url: ServerUrl::parse(&format!("http://127.0.0.1:{}", port)).unwrap(),
+        };
+        let started = Instant::now();
+        let err = process
+            .wait_until_ready(Duration::from_millis(300))
+            .unwrap_err();
+        assert!(err.to_string().contains("not ready"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,215 +1,226 @@
+use super::error::*;
 use super::reqs::*;
+use super::transport::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+const IS_DISPLAYED_ATOM: &str = include_str!("atoms/is_displayed.js");
 
 ///WebElement
 ///
 /// Provides methods to interact with page elements.
+///
+/// The elements returned by Browser::find_element and Element::find_element_from_self remember
+/// the locators they were found with. If a command fails with the StaleElementReference error
+/// (e.g. the page was re-rendered), such an element is found again with the same locators
+/// and the command is retried once. Call set_auto_refresh(false) to get the error instead.
+/// The elements returned by the other methods (e.g. find_elements) are never refreshed.
 #[derive(Debug)]
 pub struct Element {
-    pub(crate) ip: String,
-    pub(crate) port: String,
+    pub(crate) transport: Arc<dyn Transport>,
     pub(crate) element_gr_id: String,
-    pub(crate) element_id: String,
-    pub(crate) element_url: String,
+    element_id: RwLock<String>,
+    session_url: String,
+    locators: Option<Vec<LocatorStrategy<String>>>,
+    auto_refresh: bool,
+}
+//Serialized as the W3C web element reference, e.g. to be passed to Browser::execute_script
+impl Serialize for Element {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.element_gr_id, &self.element_id())?;
+        map.end()
+    }
 }
 impl Element {
-    ///Returns the first child element which is found using the locator.
-    pub fn find_element_from_self(&self, locator: LocatorStrategy) -> Result<Element, String> {
-        let url = format!("{}/element", self.element_url);
-        let body = body_for_find_element(locator);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let map: HashMap<String, String> = serde_json::from_str(&resp).unwrap();
-        let res = map.iter().next().unwrap();
-        let el_url = self.element_url.split("/element").next().unwrap();
+    pub(crate) fn from_reference(
+        transport: Arc<dyn Transport>,
+        session_url: &str,
+        reference: serde_json::Value,
+    ) -> Result<Element, WebDriverError> {
+        let (gr_id, id) = element_reference(reference)?;
         Ok(Element {
-            ip: self.ip.clone(),
-            port: self.port.clone(),
-            element_gr_id: res.0.clone(),
-            element_id: res.1.clone(),
-            element_url: format!("{}/element/{}", el_url, res.1.clone()),
+            transport,
+            element_gr_id: gr_id,
+            element_id: RwLock::new(id),
+            session_url: String::from(session_url),
+            locators: None,
+            auto_refresh: true,
         })
     }
-    ///Returns all children elements which are found using the locator.
-    pub fn find_elements_from_self(
+    //The locators chain from the document to the element, used to find it again when it gets stale
+    pub(crate) fn located_by(mut self, locators: Vec<LocatorStrategy<String>>) -> Self {
+        self.locators = Some(locators);
+        self
+    }
+    pub(crate) fn element_id(&self) -> String {
+        self.element_id.read().unwrap().clone()
+    }
+    pub(crate) fn element_url(&self) -> String {
+        format!("{}/element/{}", self.session_url, self.element_id())
+    }
+    pub(crate) fn session_url(&self) -> &str {
+        &self.session_url
+    }
+    ///Enables (the default) or disables finding the element again when it gets stale
+    pub fn set_auto_refresh(&mut self, auto_refresh: bool) -> &mut Self {
+        self.auto_refresh = auto_refresh;
+        self
+    }
+    fn command(
         &self,
-        locator: LocatorStrategy,
-    ) -> Result<Vec<Element>, String> {
-        let mut result = vec![];
-        let url = format!("{}/elements", self.element_url);
-        let body = body_for_find_element(locator);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let resp = parse_value(&resp);
-        let map: Vec<HashMap<String, String>> = serde_json::from_str(&resp).unwrap();
-        let element_ur = self.element_url.split("/element").next().unwrap();
-        for i in map {
-            let element_ur = element_ur.clone();
-            let res = i.iter().next().unwrap();
-            result.push(Element {
-                ip: self.ip.clone(),
-                port: self.port.clone(),
-                element_gr_id: res.0.clone(),
-                element_id: res.1.clone(),
-                element_url: format!("{}/element/{}", element_ur, res.1.clone()),
-            });
-        }
-        Ok(result)
+        method: Method,
+        endpoint: &str,
+        body: &str,
+    ) -> Result<serde_json::Value, WebDriverError> {
+        self.retry_if_stale(|| {
+            let url = format!("{}/{}", self.element_url(), endpoint);
+            send_command(&*self.transport, method, &url, body)
+        })
     }
-    pub fn is_selected(&self) -> Result<bool, String> {
-        let url = format!("{}/selected", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
+    fn retry_if_stale<T, F>(&self, send: F) -> Result<T, WebDriverError>
+    where
+        F: Fn() -> Result<T, WebDriverError>,
+    {
+        match send() {
+            Err(WebDriverError::StaleElementReference(details)) if self.can_refresh() => {
+                //If the element is gone for good, the original error is more telling
+                if self.refresh().is_err() {
+                    return Err(WebDriverError::StaleElementReference(details));
+                }
+                send()
+            }
+            result => result,
         }
-        let map: HashMap<&str, bool> = serde_json::from_str(&resp).unwrap();
-        Ok(*map.get("value").unwrap())
-    }
-    pub fn get_attribute(&self, attribute_name: &str) -> Result<String, String> {
-        let url = format!("{}/attribute/{}", self.element_url, attribute_name);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        if resp.as_bytes() == br#"{"value":null}"# {
-            return Ok("null".to_string());
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).clone())
     }
-    ///Due to the large number of structure variants that may be returned by this function,
-    /// parsing the String response to the necessary type is left for the lib users
-    pub fn get_property(&self, property_name: &str) -> Result<String, String> {
-        let url = format!("{}/property/{}", self.element_url, property_name);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        if resp.as_bytes() == br#"{"value":null}"# {
+    fn can_refresh(&self) -> bool {
+        self.auto_refresh && self.locators.is_some()
+    }
+    fn refresh(&self) -> Result<(), WebDriverError> {
+        let locators = self.locators.as_ref().unwrap();
+        let mut url = format!("{}/element", self.session_url);
+        let mut id = String::new();
+        for locator in locators {
+            let body = body_for_find_element(locator);
+            let resp = send_command(&*self.transport, Method::POST, &url, &body)?;
+            id = element_reference(resp)?.1;
+            url = format!("{}/element/{}/element", self.session_url, id);
+        }
+        *self.element_id.write().unwrap() = id;
+        Ok(())
+    }
+    ///Returns the first child element which is found using the locator.
+    pub fn find_element_from_self<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        let body = body_for_find_element(&locator);
+        let resp = self.command(Method::POST, "element", &body)?;
+        let mut element = Element::from_reference(self.transport.clone(), &self.session_url, resp)?;
+        if let Some(locators) = &self.locators {
+            let mut locators = locators.clone();
+            locators.push(locator.to_owned_locator());
+            element = element.located_by(locators);
+        }
+        element.auto_refresh = self.auto_refresh;
+        Ok(element)
+    }
+    ///Returns all children elements which are found using the locator.
+    pub fn find_elements_from_self<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        let body = body_for_find_element(&locator);
+        let resp = self.command(Method::POST, "elements", &body)?;
+        let refs: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        refs.into_iter()
+            .map(|r| Element::from_reference(self.transport.clone(), &self.session_url, r))
+            .collect()
+    }
+    pub fn is_selected(&self) -> Result<bool, WebDriverError> {
+        let resp = self.command(Method::GET, "selected", "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Returns "null" if the element does not have the attribute
+    pub fn get_attribute(&self, attribute_name: &str) -> Result<String, WebDriverError> {
+        let endpoint = format!("attribute/{}", attribute_name);
+        let resp = self.command(Method::GET, &endpoint, "")?;
+        if resp.is_null() {
             return Ok(String::from("null"));
-        } else {
-            Ok(resp)
         }
+        Ok(serde_json::from_value(resp)?)
     }
-    ///The logic behind returning json is the same as for get_property method
-    pub fn get_css_value(&self, css_property_name: &str) -> Result<String, String> {
-        let url = format!("{}/css/{}", self.element_url, css_property_name);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        } else {
-            Ok(resp)
-        }
+    ///Due to the large number of structure variants that may be returned by this function,
+    /// parsing the String response to the necessary type is left for the lib users.
+    /// The json of the property value is returned, "null" if there is no such property
+    pub fn get_property(&self, property_name: &str) -> Result<String, WebDriverError> {
+        let endpoint = format!("property/{}", property_name);
+        let resp = self.command(Method::GET, &endpoint, "")?;
+        Ok(resp.to_string())
     }
-    pub fn get_element_text(&self) -> Result<String, String> {
-        let url = format!("{}/text", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).clone())
-    }
-    pub fn get_tag_name(&self) -> Result<String, String> {
-        let url = format!("{}/name", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).clone())
+    pub fn get_css_value(&self, css_property_name: &str) -> Result<String, WebDriverError> {
+        let endpoint = format!("css/{}", css_property_name);
+        let resp = self.command(Method::GET, &endpoint, "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub fn get_element_text(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "text", "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub fn get_tag_name(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "name", "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///Returns the element's size(hight,width) and position(x-axis and y-axis)
-    pub fn get_element_rect(&self) -> Result<ElementRect, String> {
-        let url = format!("{}/rect", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, ElementRect> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).clone())
-    }
-    pub fn is_enabled(&self) -> Result<bool, String> {
-        let url = format!("{}/enabled", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, bool> = serde_json::from_str(&resp).unwrap();
-        Ok(*map.get("value").unwrap())
+    pub fn get_element_rect(&self) -> Result<ElementRect, WebDriverError> {
+        let resp = self.command(Method::GET, "rect", "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    ///Checks if the user can see the element: it is not hidden by the styles, has a positive size
+    /// and is not hidden by the overflow of its ancestors.
+    ///
+    /// The W3C spec leaves the displayedness endpoint optional, so the check is made by a script
+    /// approximating the Selenium isDisplayed atom, which may differ from it in the edge cases.
+    pub fn is_displayed(&self) -> Result<bool, WebDriverError> {
+        let resp = self.retry_if_stale(|| {
+            let url = format!("{}/execute/sync", self.session_url);
+            let script = format!(
+                "/* isDisplayed */return ({}).apply(null, arguments);",
+                IS_DISPLAYED_ATOM
+            );
+            let body = serde_json::json!({
+                "script": script,
+                "args": [{ self.element_gr_id.as_str(): self.element_id() }]
+            })
+            .to_string();
+            send_command(&*self.transport, Method::POST, &url, &body)
+        })?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub fn is_enabled(&self) -> Result<bool, WebDriverError> {
+        let resp = self.command(Method::GET, "enabled", "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///As of 06.11.2020 computed role and computed label are not implemented
     /// by chrome and geckodrivers, so this method will only be returning errors for now
-    pub fn get_computed_role(&self) -> Result<String, String> {
-        let url = format!("{}/computedrole", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).clone())
+    pub fn get_computed_role(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "computedrole", "")?;
+        Ok(serde_json::from_value(resp)?)
     }
     ///See above
-    pub fn get_computed_label(&self) -> Result<String, String> {
-        let url = format!("{}/computedlabel", self.element_url);
-        let resp = send_and_read_body(&self.ip, &self.port, Method::GET, &url, vec![], "");
-        if resp.contains("error") {
-            return Err(resp);
-        }
-        let map: HashMap<&str, String> = serde_json::from_str(&resp).unwrap();
-        Ok((*map.get("value").unwrap()).clone())
-    }
-    pub fn click(&self) -> Result<(), String> {
-        let body = r#"{}"#;
-        let url = format!("{}/click", self.element_url);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn get_computed_label(&self) -> Result<String, WebDriverError> {
+        let resp = self.command(Method::GET, "computedlabel", "")?;
+        Ok(serde_json::from_value(resp)?)
+    }
+    pub fn click(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "click", "{}")?;
         Ok(())
     }
     ///Clears any element text
-    pub fn clear_element(&self) -> Result<(), String> {
-        let body = r#"{}"#;
-        let url = format!("{}/clear", self.element_url);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn clear_element(&self) -> Result<(), WebDriverError> {
+        self.command(Method::POST, "clear", "{}")?;
         Ok(())
     }
     ///Sends the text to the element if it is possibe for the element, otherwise, returns error
@@ -222,20 +233,9 @@ impl Element {
     /// el.send_keys("Sup!").unwrap();
     /// br.close_browser().unwrap();
     /// ```
-    pub fn send_keys(&self, message: &str) -> Result<(), String> {
-        let body = format!(r#"{{"text":"{}"}}"#, message);
-        let url = format!("{}/value", self.element_url);
-        let resp = send_and_read_body(
-            &self.ip,
-            &self.port,
-            Method::POST,
-            &url,
-            cont_length_header(&body),
-            &body,
-        );
-        if resp.contains("error") {
-            return Err(resp);
-        }
+    pub fn send_keys(&self, message: &str) -> Result<(), WebDriverError> {
+        let body = serde_json::json!({ "text": message }).to_string();
+        self.command(Method::POST, "value", &body)?;
         Ok(())
     }
 }
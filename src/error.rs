@@ -0,0 +1,229 @@
+use serde::Deserialize;
+use std::fmt;
+
+///The payload the server sends back when a command fails
+///
+/// For the list of error codes and their meaning please see https://www.w3.org/TR/webdriver/#errors
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ErrorDetails {
+    pub(crate) error: String,
+    pub(crate) message: String,
+    #[serde(default)]
+    pub(crate) stacktrace: String,
+    #[serde(default)]
+    pub(crate) data: Option<serde_json::Value>,
+}
+impl ErrorDetails {
+    ///The W3C error code, e.g. "no such element"
+    pub fn get_error(&self) -> &str {
+        &self.error
+    }
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+    pub fn get_stacktrace(&self) -> &str {
+        &self.stacktrace
+    }
+    ///Additional vendor specific data, e.g. the alert text for "unexpected alert open"
+    pub fn get_data(&self) -> Option<&serde_json::Value> {
+        self.data.as_ref()
+    }
+}
+
+///The error type returned by all Browser and Element methods
+///
+/// Every W3C error code has its own variant carrying the details sent by the server,
+/// so the failures can be matched by kind:
+/// ```
+/// # use selenium_webdriver::*;
+/// fn is_missing(res: &Result<Element, WebDriverError>) -> bool {
+///     matches!(res, Err(WebDriverError::NoSuchElement(_)))
+/// }
+/// ```
+/// More variants may be added, e.g. WebDriverError::Image comes with the "image" feature,
+/// so the matches over all the variants need a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WebDriverError {
+    ElementClickIntercepted(ErrorDetails),
+    ElementNotInteractable(ErrorDetails),
+    InsecureCertificate(ErrorDetails),
+    InvalidArgument(ErrorDetails),
+    InvalidCookieDomain(ErrorDetails),
+    InvalidElementState(ErrorDetails),
+    InvalidSelector(ErrorDetails),
+    InvalidSessionId(ErrorDetails),
+    JavascriptError(ErrorDetails),
+    MoveTargetOutOfBounds(ErrorDetails),
+    NoSuchAlert(ErrorDetails),
+    NoSuchCookie(ErrorDetails),
+    NoSuchElement(ErrorDetails),
+    NoSuchFrame(ErrorDetails),
+    NoSuchWindow(ErrorDetails),
+    NoSuchShadowRoot(ErrorDetails),
+    ScriptTimeout(ErrorDetails),
+    SessionNotCreated(ErrorDetails),
+    StaleElementReference(ErrorDetails),
+    DetachedShadowRoot(ErrorDetails),
+    Timeout(ErrorDetails),
+    UnableToSetCookie(ErrorDetails),
+    UnableToCaptureScreen(ErrorDetails),
+    UnexpectedAlertOpen(ErrorDetails),
+    UnknownCommand(ErrorDetails),
+    UnknownMethod(ErrorDetails),
+    UnsupportedOperation(ErrorDetails),
+    ///"unknown error" as well as any error code not defined by the W3C spec
+    UnknownError(ErrorDetails),
+    ///The server could not be reached or the connection broke
+    Transport(std::io::Error),
+    ///The response could not be decoded into the expected type
+    Json(serde_json::Error),
+    ///The response does not look like a WebDriver response
+    InvalidResponse(String),
+    ///Reading or writing a local file failed, e.g. when saving a screenshot
+    Io(std::io::Error),
+    ///Decoding or encoding a screenshot failed, available with the "image" feature
+    #[cfg(feature = "image")]
+    Image(image::ImageError),
+    ///The server url could not be parsed
+    InvalidUrl(String),
+    ///The local driver process could not be started or did not become ready
+    Driver(String),
+    ///The session was closed by close_browser, so the command was not sent
+    SessionClosed,
+}
+impl WebDriverError {
+    pub(crate) fn from_details(details: ErrorDetails) -> Self {
+        match details.error.as_str() {
+            "element click intercepted" => WebDriverError::ElementClickIntercepted(details),
+            "element not interactable" => WebDriverError::ElementNotInteractable(details),
+            "insecure certificate" => WebDriverError::InsecureCertificate(details),
+            "invalid argument" => WebDriverError::InvalidArgument(details),
+            "invalid cookie domain" => WebDriverError::InvalidCookieDomain(details),
+            "invalid element state" => WebDriverError::InvalidElementState(details),
+            "invalid selector" => WebDriverError::InvalidSelector(details),
+            "invalid session id" => WebDriverError::InvalidSessionId(details),
+            "javascript error" => WebDriverError::JavascriptError(details),
+            "move target out of bounds" => WebDriverError::MoveTargetOutOfBounds(details),
+            "no such alert" => WebDriverError::NoSuchAlert(details),
+            "no such cookie" => WebDriverError::NoSuchCookie(details),
+            "no such element" => WebDriverError::NoSuchElement(details),
+            "no such frame" => WebDriverError::NoSuchFrame(details),
+            "no such window" => WebDriverError::NoSuchWindow(details),
+            "no such shadow root" => WebDriverError::NoSuchShadowRoot(details),
+            "script timeout" => WebDriverError::ScriptTimeout(details),
+            "session not created" => WebDriverError::SessionNotCreated(details),
+            "stale element reference" => WebDriverError::StaleElementReference(details),
+            "detached shadow root" => WebDriverError::DetachedShadowRoot(details),
+            "timeout" => WebDriverError::Timeout(details),
+            "unable to set cookie" => WebDriverError::UnableToSetCookie(details),
+            "unable to capture screen" => WebDriverError::UnableToCaptureScreen(details),
+            "unexpected alert open" => WebDriverError::UnexpectedAlertOpen(details),
+            "unknown command" => WebDriverError::UnknownCommand(details),
+            "unknown method" => WebDriverError::UnknownMethod(details),
+            "unsupported operation" => WebDriverError::UnsupportedOperation(details),
+            _ => WebDriverError::UnknownError(details),
+        }
+    }
+    ///Returns the details sent by the server if the error came from the server
+    pub fn details(&self) -> Option<&ErrorDetails> {
+        match self {
+            WebDriverError::ElementClickIntercepted(d)
+            | WebDriverError::ElementNotInteractable(d)
+            | WebDriverError::InsecureCertificate(d)
+            | WebDriverError::InvalidArgument(d)
+            | WebDriverError::InvalidCookieDomain(d)
+            | WebDriverError::InvalidElementState(d)
+            | WebDriverError::InvalidSelector(d)
+            | WebDriverError::InvalidSessionId(d)
+            | WebDriverError::JavascriptError(d)
+            | WebDriverError::MoveTargetOutOfBounds(d)
+            | WebDriverError::NoSuchAlert(d)
+            | WebDriverError::NoSuchCookie(d)
+            | WebDriverError::NoSuchElement(d)
+            | WebDriverError::NoSuchFrame(d)
+            | WebDriverError::NoSuchWindow(d)
+            | WebDriverError::NoSuchShadowRoot(d)
+            | WebDriverError::ScriptTimeout(d)
+            | WebDriverError::SessionNotCreated(d)
+            | WebDriverError::StaleElementReference(d)
+            | WebDriverError::DetachedShadowRoot(d)
+            | WebDriverError::Timeout(d)
+            | WebDriverError::UnableToSetCookie(d)
+            | WebDriverError::UnableToCaptureScreen(d)
+            | WebDriverError::UnexpectedAlertOpen(d)
+            | WebDriverError::UnknownCommand(d)
+            | WebDriverError::UnknownMethod(d)
+            | WebDriverError::UnsupportedOperation(d)
+            | WebDriverError::UnknownError(d) => Some(d),
+            _ => None,
+        }
+    }
+    ///Returns the W3C error code (e.g. "stale element reference") if the error came from the server
+    pub fn error_code(&self) -> Option<&str> {
+        self.details().map(|d| d.error.as_str())
+    }
+}
+impl fmt::Display for WebDriverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebDriverError::Transport(e) => write!(f, "transport error: {}", e),
+            WebDriverError::Json(e) => write!(f, "could not decode the response: {}", e),
+            WebDriverError::InvalidResponse(m) => write!(f, "invalid response: {}", m),
+            WebDriverError::Io(e) => write!(f, "io error: {}", e),
+            #[cfg(feature = "image")]
+            WebDriverError::Image(e) => write!(f, "image error: {}", e),
+            WebDriverError::InvalidUrl(m) => write!(f, "invalid url: {}", m),
+            WebDriverError::Driver(m) => write!(f, "driver error: {}", m),
+            WebDriverError::SessionClosed => write!(f, "the session is closed"),
+            _ => {
+                let d = self.details().unwrap();
+                write!(f, "{}: {}", d.error, d.message)
+            }
+        }
+    }
+}
+impl std::error::Error for WebDriverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WebDriverError::Transport(e) | WebDriverError::Io(e) => Some(e),
+            WebDriverError::Json(e) => Some(e),
+            #[cfg(feature = "image")]
+            WebDriverError::Image(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for WebDriverError {
+    fn from(e: std::io::Error) -> Self {
+        WebDriverError::Transport(e)
+    }
+}
+impl From<serde_json::Error> for WebDriverError {
+    fn from(e: serde_json::Error) -> Self {
+        WebDriverError::Json(e)
+    }
+}
+
+mod error_tests {
+    use super::*;
+    #[test]
+    fn codes_to_variants() {
+        let details: ErrorDetails = serde_json::from_str(
+            r#"{"error":"stale element reference","message":"gone","stacktrace":""}"#,
+        )
+        .unwrap();
+        let err = WebDriverError::from_details(details);
+        assert!(matches!(err, WebDriverError::StaleElementReference(_)));
+        assert_eq!(err.error_code(), Some("stale element reference"));
+        assert_eq!(err.to_string(), "stale element reference: gone");
+    }
+    #[test]
+    fn unknown_code() {
+        let details: ErrorDetails =
+            serde_json::from_str(r#"{"error":"made up","message":"?"}"#).unwrap();
+        let err = WebDriverError::from_details(details);
+        assert!(matches!(err, WebDriverError::UnknownError(_)));
+        assert_eq!(err.details().unwrap().get_stacktrace(), "");
+    }
+}
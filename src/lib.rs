@@ -3,25 +3,77 @@
 //! The main purpose of this crate is to provide convinient ways to communicate with selenium server
 //! and write automated UI tests in Rust.
 //! To start using it, you need to have the selenium server running on localhost:4444 and chromedriver or geckodriver
-//! be present in your path
+//! be present in your path.
+//! Alternatively, the DriverService may launch the driver itself, see Browser::start_session_with_service.
+//The code generated by the PageObject derive refers to the crate by its name, this lets it work inside the crate too
+extern crate self as selenium_webdriver;
 mod actions;
+#[cfg(feature = "async")]
+mod asyncbrowser;
+#[cfg(feature = "async")]
+mod asyncelement;
+#[cfg(feature = "async")]
+mod asynctransport;
 mod browser;
+mod by;
 mod capabilities;
 mod chromeoptions;
+mod driverservice;
 mod element;
+mod error;
 mod firefoxoptions;
+mod pageobject;
 mod proxy;
+mod relativelocator;
 mod reqs;
 mod safarioptions;
+#[cfg(feature = "image")]
+mod screenshot;
+mod script;
+mod select;
+mod serverurl;
+mod shadowroot;
 mod specialkey;
+mod transport;
+mod upload;
+#[cfg(feature = "image")]
+mod visual;
+mod wait;
+mod wirelog;
 
 pub use actions::*;
+#[cfg(feature = "async")]
+pub use asyncbrowser::*;
+#[cfg(feature = "async")]
+pub use asyncelement::*;
+#[cfg(feature = "async")]
+pub use asynctransport::*;
 pub use browser::*;
+pub use by::*;
 pub use capabilities::*;
 pub use chromeoptions::*;
+pub use driverservice::*;
 pub use element::*;
+pub use error::*;
 pub use firefoxoptions::*;
+#[cfg(feature = "image")]
+pub use image;
+pub use pageobject::*;
 pub use proxy::*;
+pub use relativelocator::*;
 pub use reqs::*;
 pub use safarioptions::*;
+#[cfg(feature = "image")]
+pub use screenshot::*;
+pub use script::*;
+pub use select::*;
+#[cfg(feature = "derive")]
+pub use selenium_webdriver_derive::PageObject;
+pub use serverurl::*;
+pub use shadowroot::*;
 pub use specialkey::*;
+pub use transport::*;
+#[cfg(feature = "image")]
+pub use visual::*;
+pub use wait::*;
+pub use wirelog::*;
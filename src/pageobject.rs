@@ -0,0 +1,223 @@
+use super::browser::*;
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+use super::shadowroot::*;
+use std::marker::PhantomData;
+
+///Anything the elements can be found in: the Browser (the whole page), an Element (its children) or a ShadowRoot
+pub trait SearchContext {
+    fn find_element<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError>;
+    fn find_elements<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError>;
+}
+impl SearchContext for Browser {
+    fn find_element<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        Browser::find_element(self, locator)
+    }
+    fn find_elements<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        Browser::find_elements(self, locator)
+    }
+}
+impl SearchContext for Element {
+    fn find_element<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        self.find_element_from_self(locator)
+    }
+    fn find_elements<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        self.find_elements_from_self(locator)
+    }
+}
+impl SearchContext for ShadowRoot {
+    fn find_element<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        ShadowRoot::find_element(self, locator)
+    }
+    fn find_elements<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        ShadowRoot::find_elements(self, locator)
+    }
+}
+impl<T: SearchContext + ?Sized> SearchContext for &T {
+    fn find_element<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        (**self).find_element(locator)
+    }
+    fn find_elements<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        (**self).find_elements(locator)
+    }
+}
+
+///A page or a component of it, which finds its elements in the context it was created with
+///
+/// Is usually derived with #[derive(PageObject)] (the "derive" feature), which also generates
+/// an accessor for every #[find(...)] and #[find_all(...)] field. The elements are looked up
+/// every time the accessor is called, so they are never stale. A component is a page object
+/// with the Element context, it is found by its root element and scoped to it.
+///
+/// # Examples
+/// ```no_run
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use selenium_webdriver::*;
+///
+/// #[derive(PageObject)]
+/// struct ResultItem {
+///     #[context]
+///     root: Element,
+///     #[find(css = "h3")]
+///     title: Lazy<Element>,
+/// }
+///
+/// #[derive(PageObject)]
+/// struct SearchPage<'a> {
+///     #[context]
+///     browser: &'a Browser,
+///     #[find(id = "query")]
+///     query: Lazy<Element>,
+///     #[find_all(css = ".result")]
+///     results: Lazy<Vec<ResultItem>>,
+/// }
+///
+/// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+/// let page = SearchPage::from_context(&br);
+/// page.query().unwrap().send_keys("selenium").unwrap();
+/// for item in page.results().unwrap() {
+///     println!("{}", item.title().unwrap().get_element_text().unwrap());
+/// }
+/// # }
+/// ```
+pub trait PageObject {
+    type Context: SearchContext;
+    fn from_context(context: Self::Context) -> Self;
+    fn context(&self) -> &Self::Context;
+}
+
+///Converts the found element to the type returned by a page object accessor
+pub trait FromElement {
+    fn from_element(element: Element) -> Self;
+}
+impl FromElement for Element {
+    fn from_element(element: Element) -> Self {
+        element
+    }
+}
+impl<P: PageObject<Context = Element>> FromElement for P {
+    fn from_element(element: Element) -> Self {
+        P::from_context(element)
+    }
+}
+
+///The marker of a page object field, whose value is found when the accessor is called
+pub struct Lazy<T> {
+    target: PhantomData<fn() -> T>,
+}
+impl<T> Lazy<T> {
+    pub fn new() -> Self {
+        Lazy {
+            target: PhantomData,
+        }
+    }
+}
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Lazy::new()
+    }
+}
+impl<T> std::fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Lazy")
+    }
+}
+
+#[cfg(feature = "derive")]
+mod page_object_tests {
+    use super::*;
+    use crate::transport::*;
+    use crate::PageObject;
+
+    #[derive(PageObject)]
+    struct ResultItem {
+        #[context]
+        root: Element,
+        #[find(css = "h3")]
+        title: Lazy<Element>,
+    }
+
+    #[derive(PageObject)]
+    struct SearchPage<'a> {
+        #[context]
+        browser: &'a Browser,
+        #[find(id = "query")]
+        query: Lazy<Element>,
+        #[find_all(css = ".result")]
+        results: Lazy<Vec<ResultItem>>,
+        visits: u32,
+    }
+
+    #[test]
+    fn derived_accessors() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"q"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/elements",
+            r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"r1"}]}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/r1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"t1"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/t1/text",
+            r#"{"value":"First"}"#,
+        );
+        let page = SearchPage::from_context(&br);
+        assert_eq!(page.visits, 0);
+        assert_eq!(page.query().unwrap().element_id(), "q");
+        let results = page.results().unwrap();
+        let title = results[0].title().unwrap();
+        assert_eq!(title.get_element_text().unwrap(), "First");
+        assert!(mock.is_done());
+        let requests = mock.requests();
+        assert_eq!(
+            requests[1].get_body(),
+            r##"{"using":"css selector","value":"#query"}"##
+        );
+        assert_eq!(
+            requests[3].get_body(),
+            r#"{"using":"css selector","value":"h3"}"#
+        );
+    }
+}
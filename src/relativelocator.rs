@@ -0,0 +1,272 @@
+use super::browser::*;
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+
+//Returns the rects of the arguments in the page coordinates, the same way the rect endpoint computes them
+const RECTS_SCRIPT: &str = "return Array.prototype.map.call(arguments, function(e) { var r = e.getBoundingClientRect(); return [r.left + window.pageXOffset, r.top + window.pageYOffset, r.width, r.height]; });";
+
+#[derive(Debug, Clone, Copy)]
+enum Relation {
+    Above,
+    Below,
+    LeftOf,
+    RightOf,
+    Near(f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+}
+impl Rect {
+    fn right(&self) -> f64 {
+        self.left + self.width
+    }
+    fn bottom(&self) -> f64 {
+        self.top + self.height
+    }
+    fn center(&self) -> (f64, f64) {
+        (self.left + self.width / 2.0, self.top + self.height / 2.0)
+    }
+    //The shortest distance between the edges, 0 if the rects overlap
+    fn gap(&self, other: &Rect) -> f64 {
+        let dx = (other.left - self.right())
+            .max(self.left - other.right())
+            .max(0.0);
+        let dy = (other.top - self.bottom())
+            .max(self.top - other.bottom())
+            .max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+    fn center_distance(&self, other: &Rect) -> f64 {
+        let (x1, y1) = self.center();
+        let (x2, y2) = other.center();
+        ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt()
+    }
+    fn satisfies(&self, relation: Relation, anchor: &Rect) -> bool {
+        match relation {
+            Relation::Above => self.bottom() <= anchor.top,
+            Relation::Below => self.top >= anchor.bottom(),
+            Relation::LeftOf => self.right() <= anchor.left,
+            Relation::RightOf => self.left >= anchor.right(),
+            Relation::Near(distance) => self.gap(anchor) <= distance,
+        }
+    }
+}
+impl From<ElementRect> for Rect {
+    fn from(rect: ElementRect) -> Self {
+        Rect {
+            left: rect.x as f64,
+            top: rect.y as f64,
+            width: rect.width as f64,
+            height: rect.height as f64,
+        }
+    }
+}
+
+///Finds the elements by their position relative to the other elements, like Selenium 4 does
+///
+/// The elements matching the base locator are filtered by all the constraints
+/// and ordered by the distance to the closest anchor, the nearest first. The anchors themselves are never returned.
+/// The geometry is taken on the client: the anchors' rects with Element::get_element_rect
+/// and the candidates' ones with a single execute_sync call.
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+/// br.open("https://vk.com").unwrap();
+/// let password = br.find_element(By::id("index_pass")).unwrap();
+/// let mut locator = RelativeLocator::new(LocatorStrategy::TAGNAME("input"));
+/// locator.above(&password);
+/// let email = br.find_element_relative(&locator).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RelativeLocator<'a, S: AsRef<str> = &'static str> {
+    base: LocatorStrategy<S>,
+    constraints: Vec<(Relation, &'a Element)>,
+}
+impl<'a, S: AsRef<str>> RelativeLocator<'a, S> {
+    pub fn new(base: LocatorStrategy<S>) -> Self {
+        RelativeLocator {
+            base,
+            constraints: vec![],
+        }
+    }
+    ///The bottom of the element is not lower than the top of the anchor
+    pub fn above(&mut self, anchor: &'a Element) -> &mut Self {
+        self.constraints.push((Relation::Above, anchor));
+        self
+    }
+    ///The top of the element is not higher than the bottom of the anchor
+    pub fn below(&mut self, anchor: &'a Element) -> &mut Self {
+        self.constraints.push((Relation::Below, anchor));
+        self
+    }
+    ///The right edge of the element is not to the right of the anchor's left edge
+    pub fn left_of(&mut self, anchor: &'a Element) -> &mut Self {
+        self.constraints.push((Relation::LeftOf, anchor));
+        self
+    }
+    ///The left edge of the element is not to the left of the anchor's right edge
+    pub fn right_of(&mut self, anchor: &'a Element) -> &mut Self {
+        self.constraints.push((Relation::RightOf, anchor));
+        self
+    }
+    ///The element is not further than 50 px from the anchor
+    pub fn near(&mut self, anchor: &'a Element) -> &mut Self {
+        self.near_within(anchor, 50.0)
+    }
+    ///The element is not further than the distance (in px) from the anchor
+    pub fn near_within(&mut self, anchor: &'a Element, distance: f64) -> &mut Self {
+        self.constraints.push((Relation::Near(distance), anchor));
+        self
+    }
+}
+impl Browser {
+    ///Returns the elements matching the relative locator, the nearest first
+    pub fn find_elements_relative<S: AsRef<str>>(
+        &self,
+        locator: &RelativeLocator<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        let mut anchors = vec![];
+        for (relation, anchor) in &locator.constraints {
+            anchors.push((
+                *relation,
+                anchor.element_id(),
+                Rect::from(anchor.get_element_rect()?),
+            ));
+        }
+        let candidates: Vec<Element> = self
+            .find_elements(locator.base.to_owned_locator())?
+            .into_iter()
+            .filter(|el| anchors.iter().all(|(_, id, _)| *id != el.element_id()))
+            .collect();
+        if candidates.is_empty() {
+            return Ok(candidates);
+        }
+        let args: Vec<String> = candidates
+            .iter()
+            .map(|el| serde_json::json!({ &el.element_gr_id: el.element_id() }).to_string())
+            .collect();
+        let rects = self.execute_sync(RECTS_SCRIPT, &args.iter().map(|a| a.as_str()).collect())?;
+        let rects: Vec<[f64; 4]> = serde_json::from_str(&rects)?;
+        if rects.len() != candidates.len() {
+            return Err(WebDriverError::InvalidResponse(String::from(
+                "The number of rects does not match the number of elements",
+            )));
+        }
+        let mut matching: Vec<(f64, Element)> = candidates
+            .into_iter()
+            .zip(rects)
+            .filter_map(|(el, [left, top, width, height])| {
+                let rect = Rect {
+                    left,
+                    top,
+                    width,
+                    height,
+                };
+                if !anchors
+                    .iter()
+                    .all(|(relation, _, a)| rect.satisfies(*relation, a))
+                {
+                    return None;
+                }
+                let distance = anchors
+                    .iter()
+                    .map(|(_, _, a)| rect.center_distance(a))
+                    .fold(f64::INFINITY, f64::min);
+                Some((distance, el))
+            })
+            .collect();
+        matching.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(matching.into_iter().map(|(_, el)| el).collect())
+    }
+    ///Returns the nearest element matching the relative locator, the NoSuchElement error if there is none
+    pub fn find_element_relative<S: AsRef<str>>(
+        &self,
+        locator: &RelativeLocator<S>,
+    ) -> Result<Element, WebDriverError> {
+        self.find_elements_relative(locator)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                WebDriverError::NoSuchElement(ErrorDetails {
+                    error: String::from("no such element"),
+                    message: String::from("No element matches the relative locator"),
+                    stacktrace: String::new(),
+                    data: None,
+                })
+            })
+    }
+}
+
+mod relative_locator_tests {
+    use super::*;
+    use crate::transport::*;
+    #[test]
+    fn rect_relations() {
+        let anchor = Rect {
+            left: 100.0,
+            top: 100.0,
+            width: 50.0,
+            height: 20.0,
+        };
+        let above = Rect {
+            left: 100.0,
+            top: 60.0,
+            width: 50.0,
+            height: 40.0,
+        };
+        assert!(above.satisfies(Relation::Above, &anchor));
+        assert!(!above.satisfies(Relation::Below, &anchor));
+        assert!(above.satisfies(Relation::Near(0.0), &anchor));
+        let far_right = Rect {
+            left: 230.0,
+            top: 100.0,
+            width: 10.0,
+            height: 10.0,
+        };
+        assert!(far_right.satisfies(Relation::RightOf, &anchor));
+        assert!(!far_right.satisfies(Relation::Near(50.0), &anchor));
+        assert_eq!(far_right.gap(&anchor), 80.0);
+    }
+    #[test]
+    fn filters_and_orders_by_proximity() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"pass"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/pass/rect",
+            r#"{"value":{"x":0.0,"y":200.0,"width":100,"height":20}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/elements",
+            r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"far"},{"element-6066-11e4-a52e-4f735466cecf":"pass"},{"element-6066-11e4-a52e-4f735466cecf":"near"},{"element-6066-11e4-a52e-4f735466cecf":"under"}]}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[[0,10,100,20],[0,150,100,20],[0,300,100,20]]}"#,
+        );
+        let pass = br.find_element(LocatorStrategy::CSS("#pass")).unwrap();
+        let mut locator = RelativeLocator::new(LocatorStrategy::TAGNAME("input"));
+        locator.above(&pass);
+        let found = br.find_elements_relative(&locator).unwrap();
+        let ids: Vec<String> = found.iter().map(|el| el.element_id()).collect();
+        assert_eq!(ids, vec!["near", "far"]);
+        assert!(mock.is_done());
+        let body = mock.requests()[4].get_body().to_string();
+        assert!(body.contains(r#"[{"element-6066-11e4-a52e-4f735466cecf":"far"},"#));
+        assert!(!body.contains(r#""pass""#));
+    }
+}
@@ -1,35 +1,78 @@
-use std::error::Error;
-use std::io::{Read, Write};
-use std::net::TcpStream;
+use super::error::*;
+use super::transport::*;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
 
-pub(crate) enum Method {
+///HTTP methods used by the WebDriver protocol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
     GET,
     POST,
     DELETE,
 }
-pub enum LocatorStrategy {
-    CSS(&'static str),
-    LINKTEXT(&'static str),
-    PARTLINKTEXT(&'static str),
-    TAGNAME(&'static str),
-    XPATH(&'static str),
+///The way to locate the elements, see https://www.w3.org/TR/webdriver/#locator-strategies
+///
+/// The value can be any string, borrowed or owned, so the locators may be built at runtime.
+/// It is sent as a json string, so quotes and backslashes need no escaping.
+///
+/// # Examples
+/// ```
+/// # use selenium_webdriver::*;
+/// let id = 42;
+/// let by_data_id = LocatorStrategy::CSS(format!("[data-id='{}']", id));
+/// assert_eq!(by_data_id.get_value(), "[data-id='42']");
+/// let by_text = LocatorStrategy::XPATH(r#"//a[text()="Say \"hi\""]"#);
+/// assert_eq!(by_text.get_using(), "xpath");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocatorStrategy<S: AsRef<str> = &'static str> {
+    CSS(S),
+    LINKTEXT(S),
+    PARTLINKTEXT(S),
+    TAGNAME(S),
+    XPATH(S),
 }
-pub(crate) fn send_request(
-    ip: &str,
-    port: &str,
-    method: Method,
-    path: &str,
-    headers: Vec<String>,
-    body: &str,
-) -> Result<String, Box<dyn Error>> {
-    let request = create_req(method, path, headers, body);
-    let address = format!("{}:{}", ip, port);
-    let mut connection = TcpStream::connect(address)?;
-    connection.write(request.as_bytes())?;
-    connection.flush()?;
-    let buf = read_response_to_vec_u8(connection).unwrap();
-    let st = String::from_utf8(buf).unwrap();
-    Ok(st)
+impl<S: AsRef<str>> LocatorStrategy<S> {
+    ///The W3C name of the strategy, e.g. "css selector"
+    pub fn get_using(&self) -> &'static str {
+        match self {
+            LocatorStrategy::CSS(_) => "css selector",
+            LocatorStrategy::LINKTEXT(_) => "link text",
+            LocatorStrategy::PARTLINKTEXT(_) => "partial link text",
+            LocatorStrategy::TAGNAME(_) => "tag name",
+            LocatorStrategy::XPATH(_) => "xpath",
+        }
+    }
+    pub fn get_value(&self) -> &str {
+        match self {
+            LocatorStrategy::CSS(v)
+            | LocatorStrategy::LINKTEXT(v)
+            | LocatorStrategy::PARTLINKTEXT(v)
+            | LocatorStrategy::TAGNAME(v)
+            | LocatorStrategy::XPATH(v) => v.as_ref(),
+        }
+    }
+    ///Copies the value, so the locator can be stored regardless of the lifetime of the original string
+    pub fn to_owned_locator(&self) -> LocatorStrategy<String> {
+        let value = String::from(self.get_value());
+        match self {
+            LocatorStrategy::CSS(_) => LocatorStrategy::CSS(value),
+            LocatorStrategy::LINKTEXT(_) => LocatorStrategy::LINKTEXT(value),
+            LocatorStrategy::PARTLINKTEXT(_) => LocatorStrategy::PARTLINKTEXT(value),
+            LocatorStrategy::TAGNAME(_) => LocatorStrategy::TAGNAME(value),
+            LocatorStrategy::XPATH(_) => LocatorStrategy::XPATH(value),
+        }
+    }
+}
+impl<S: AsRef<str>> Serialize for LocatorStrategy<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut body = serializer.serialize_struct("LocatorStrategy", 2)?;
+        body.serialize_field("using", self.get_using())?;
+        body.serialize_field("value", self.get_value())?;
+        body.end()
+    }
 }
 /*
 pub(crate) fn send_request_remote(ip:&str,method: Method, path: &str, headers: Vec<String>, body: &str)->Result<String,Box<dyn Error>> {
@@ -44,58 +87,76 @@ pub(crate) fn send_request_remote(ip:&str,method: Method, path: &str, headers: V
     Ok(st)
 }*/
 
-pub(crate) fn send_request_screensh(
+///Sends the command and returns the content of the "value" field of the response,
+/// turning the W3C error payload into the corresponding WebDriverError
+pub(crate) fn send_command(
+    transport: &dyn Transport,
     method: Method,
     path: &str,
-    headers: Vec<String>,
     body: &str,
-) -> Result<Vec<u8>, Box<dyn Error>> {
-    let request = create_req(method, path, headers, body);
-    let mut connection = TcpStream::connect("127.0.0.1:4444")?;
-    connection.write(request.as_bytes())?;
-    connection.flush()?;
-    Ok(read_response_screensh(connection)?)
+) -> Result<serde_json::Value, WebDriverError> {
+    let resp = transport.send(method, path, body)?;
+    parse_response(resp.status, &resp.body)
 }
-
-pub(crate) fn resp_body(response: String) -> Result<String, &'static str> {
-    let mut a = response.split("\r\n\r\n");
-    a.next();
-    if let Some(result) = a.next() {
-        return Ok(result.to_string());
-    } else {
-        Err("Can't get the response body")
+pub(crate) fn parse_response(
+    status: u16,
+    body: &[u8],
+) -> Result<serde_json::Value, WebDriverError> {
+    let success = (200..300).contains(&status);
+    let mut resp: serde_json::Value = match serde_json::from_slice(body) {
+        Ok(resp) => resp,
+        Err(_) if !success => return Err(unexpected_response(status, body)),
+        Err(e) => return Err(e.into()),
+    };
+    let value = match resp.get_mut("value") {
+        Some(value) => value.take(),
+        None => return Err(unexpected_response(status, body)),
+    };
+    if !success {
+        return match serde_json::from_value::<ErrorDetails>(value) {
+            Ok(details) => Err(WebDriverError::from_details(details)),
+            Err(_) => Err(unexpected_response(status, body)),
+        };
     }
+    Ok(value)
 }
-pub(crate) fn send_and_read_body(
-    ip: &str,
-    port: &str,
+fn unexpected_response(status: u16, body: &[u8]) -> WebDriverError {
+    WebDriverError::InvalidResponse(format!(
+        "HTTP {}: {}",
+        status,
+        String::from_utf8_lossy(body)
+    ))
+}
+///Decodes the base64 string value returned by the screenshot and print commands
+pub(crate) fn decode_base64_value(value: serde_json::Value) -> Result<Vec<u8>, WebDriverError> {
+    let encoded: String = serde_json::from_value(value)?;
+    base64::decode(encoded)
+        .map_err(|e| WebDriverError::InvalidResponse(format!("Bad base64 data: {}", e)))
+}
+
+pub(crate) fn create_req(
     method: Method,
+    host: &str,
     path: &str,
     headers: Vec<String>,
     body: &str,
 ) -> String {
-    resp_body(send_request(ip, port, method, path, headers, body).unwrap()).unwrap()
-}
-
-fn create_req(method: Method, path: &str, headers: Vec<String>, body: &str) -> String {
     let mut request = match &method {
         Method::GET => String::from("GET /"),
         Method::POST => String::from("POST /"),
         Method::DELETE => String::from("DELETE /"),
     };
     request.push_str(path);
-    request.push_str(" HTTP/1.1\r\n");
-    request.push_str("Host: 127.0.0.1\r\n");
-    if headers.len() > 0 {
-        for h in headers {
-            request.push_str(&h);
-            request.push_str("\r\n");
-        }
+    request.push_str(" HTTP/1.1\r\nHost: ");
+    request.push_str(host);
+    request.push_str("\r\n");
+    for h in headers {
+        request.push_str(&h);
+        request.push_str("\r\n");
     }
-    request.push_str("\r\n\r\n");
-    match method {
-        Method::POST => request.push_str(body),
-        _ => (),
+    request.push_str("\r\n");
+    if let Method::POST = method {
+        request.push_str(body);
     }
     request
 }
@@ -131,128 +192,185 @@ fn create_remote_req(
     request
 }
 
-fn read_response_to_vec_u8(mut stream: TcpStream) -> Result<Vec<u8>, Box<dyn Error>> {
-    use std::sync::mpsc;
-    use std::thread;
-    let mut result_buf = vec![];
-    let mut temp_buf = vec![];
-    let (sender, receiver) = mpsc::channel();
-    std::thread::spawn(move || loop {
-        let mut b = vec![0; 262144];
-        let bytes_num = stream.peek(&mut b).unwrap_or(0);
-        let mut buff = vec![0; bytes_num];
-        let _ = stream.read(&mut buff);
-        let _ = sender.send(buff);
-    });
-
-    let mut counter = 0;
-    let mut started = false;
-    loop {
-        if counter == 3 {
-            break;
-        }
-        if let Ok(a) = receiver.try_recv() {
-            temp_buf.push(a);
-            started = true;
-        } else if !started {
-            continue;
-        } else {
-            thread::sleep(std::time::Duration::from_millis(10));
-            counter += 1;
+///Status code, headers and body of an HTTP response
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub(crate) status: u16,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+impl HttpResponse {
+    pub fn new(status: u16, headers: Vec<(String, String)>, body: Vec<u8>) -> Self {
+        HttpResponse {
+            status,
+            headers,
+            body,
         }
     }
-
-    for v in temp_buf {
-        for b in v {
-            result_buf.push(b);
-        }
+    pub fn get_status(&self) -> u16 {
+        self.status
     }
-    Ok(result_buf)
-}
-fn read_response_screensh(mut stream: TcpStream) -> Result<Vec<u8>, Box<dyn Error>> {
-    use std::sync::mpsc;
-    use std::thread;
-    let mut result_buf = vec![];
-    let mut temp_buf = vec![];
-    let (sender, receiver) = mpsc::channel();
-    std::thread::spawn(move || loop {
-        let mut b = vec![0; 262144];
-        let bytes_num = stream.peek(&mut b).unwrap_or(0);
-        let mut buff = vec![0; bytes_num];
-        let _ = stream.read(&mut buff);
-        let _ = sender.send(buff);
-    });
-
-    let mut counter = 0;
-    let mut started = false;
+    pub fn get_headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+    pub fn get_body(&self) -> &[u8] {
+        &self.body
+    }
+    ///Returns the value of the first header with the given name, the name is case insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+///Reads one HTTP/1.1 response from the stream.
+///
+/// The body is delimited by Content-Length or chunked Transfer-Encoding if present,
+/// otherwise it is read until the server closes the connection.
+pub(crate) fn read_response<R: BufRead>(reader: &mut R) -> Result<HttpResponse, WebDriverError> {
     loop {
-        if counter == 3 {
-            break;
+        let mut line = read_line(reader)?;
+        //Tolerate empty lines before the status line
+        while line.is_empty() {
+            line = read_line(reader)?;
         }
-        if let Ok(a) = receiver.try_recv() {
-            temp_buf.push(a);
-            started = true;
-        } else if !started {
-            continue;
-        } else {
-            thread::sleep(std::time::Duration::from_millis(10));
-            counter += 1;
+        let status = parse_status_line(&line)?;
+        let headers = read_headers(reader)?;
+        let mut response = HttpResponse {
+            status,
+            headers,
+            body: vec![],
+        };
+        match body_length(&response)? {
+            BodyLength::Interim => continue,
+            BodyLength::Empty => (),
+            BodyLength::Chunked => response.body = read_chunked_body(reader)?,
+            BodyLength::Length(length) => {
+                response.body = vec![0; length];
+                reader.read_exact(&mut response.body)?;
+            }
+            BodyLength::UntilClose => {
+                reader.read_to_end(&mut response.body)?;
+            }
         }
+        return Ok(response);
     }
-
-    for v in temp_buf {
-        for b in v {
-            result_buf.push(b);
+}
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, WebDriverError> {
+    let mut line = vec![];
+    let read = reader.read_until(b'\n', &mut line)?;
+    line_from_bytes(line, read)
+}
+fn read_headers<R: BufRead>(reader: &mut R) -> Result<Vec<(String, String)>, WebDriverError> {
+    let mut headers = vec![];
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok(headers);
         }
+        headers.push(parse_header_line(&line)?);
     }
-    let len = result_buf.len();
-    let mut index = 0;
-    for i in 0..400 {
-        if result_buf[i] == b"{"[0]
-            && result_buf[i + 1] == b"\""[0]
-            && result_buf[i + 2] == b"v"[0]
-            && result_buf[i + 3] == b"a"[0]
-            && result_buf[i + 4] == b"l"[0]
-        {
-            index = i + 9;
-            break;
+}
+fn read_chunked_body<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, WebDriverError> {
+    let mut body = vec![];
+    loop {
+        let size = parse_chunk_size(&read_line(reader)?)?;
+        if size == 0 {
+            //Skip the trailer section
+            read_headers(reader)?;
+            return Ok(body);
         }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        read_line(reader)?;
     }
-    let mut res = vec![];
-    res.extend_from_slice(&result_buf[index + 1..len - 2]);
-    Ok(res)
 }
-pub(crate) fn cont_length_header(content: &str) -> Vec<String> {
-    vec![format!("Content-Length:{}", content.len() + 2)]
+///How the body of the response is delimited
+pub(crate) enum BodyLength {
+    ///"100 Continue" and the like, the real response follows
+    Interim,
+    Empty,
+    Length(usize),
+    Chunked,
+    UntilClose,
 }
-pub(crate) fn body_for_find_element(loc_strategy: LocatorStrategy) -> String {
-    match loc_strategy {
-        LocatorStrategy::CSS(selector) => {
-            format!(r#"{{"using":"css selector","value":"{}"}}"#, selector)
-        }
-        LocatorStrategy::LINKTEXT(selector) => {
-            format!(r#"{{"using":"link text","value":"{}"}}"#, selector)
-        }
-        LocatorStrategy::PARTLINKTEXT(selector) => {
-            format!(r#"{{"using":"partial link text","value":"{}"}}"#, selector)
-        }
-        LocatorStrategy::TAGNAME(selector) => {
-            format!(r#"{{"using":"tag name","value":"{}"}}"#, selector)
-        }
-        LocatorStrategy::XPATH(selector) => {
-            format!(r#"{{"using":"xpath","value":"{}"}}"#, selector)
-        }
+pub(crate) fn body_length(response: &HttpResponse) -> Result<BodyLength, WebDriverError> {
+    if (100..200).contains(&response.status) {
+        return Ok(BodyLength::Interim);
+    }
+    if response.status == 204 || response.status == 304 {
+        return Ok(BodyLength::Empty);
+    }
+    let chunked = response
+        .header("Transfer-Encoding")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"));
+    if chunked {
+        return Ok(BodyLength::Chunked);
+    }
+    match response.header("Content-Length") {
+        Some(length) => length.trim().parse().map(BodyLength::Length).map_err(|_| {
+            WebDriverError::InvalidResponse(format!("Bad Content-Length: {}", length))
+        }),
+        None => Ok(BodyLength::UntilClose),
+    }
+}
+///Turns the bytes read up to and including '\n' into a line without the line break
+pub(crate) fn line_from_bytes(line: Vec<u8>, read: usize) -> Result<String, WebDriverError> {
+    if read == 0 {
+        return Err(WebDriverError::Transport(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "The connection was closed before the response was complete",
+        )));
+    }
+    let line = String::from_utf8(line)
+        .map_err(|_| WebDriverError::InvalidResponse(String::from("Non UTF-8 response head")))?;
+    Ok(line.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+pub(crate) fn parse_status_line(line: &str) -> Result<u16, WebDriverError> {
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse()
+            .map_err(|_| WebDriverError::InvalidResponse(format!("Bad status line: {}", line))),
+        _ => Err(WebDriverError::InvalidResponse(format!(
+            "Bad status line: {}",
+            line
+        ))),
     }
 }
-pub(crate) fn parse_value(body: &str) -> String {
-    let resp = body
-        .replace("\n", "")
-        .replace(" ", "")
-        .replace(r#"{"value":"#, "");
-    let mut resp_vec: Vec<char> = resp.chars().collect();
-    resp_vec.pop();
-    let result: String = resp_vec.iter().collect();
-    result
+pub(crate) fn parse_header_line(line: &str) -> Result<(String, String), WebDriverError> {
+    match line.split_once(':') {
+        Some((name, value)) => Ok((name.trim().to_string(), value.trim().to_string())),
+        None => Err(WebDriverError::InvalidResponse(format!(
+            "Bad header line: {}",
+            line
+        ))),
+    }
+}
+pub(crate) fn parse_chunk_size(line: &str) -> Result<usize, WebDriverError> {
+    let size = line.split(';').next().unwrap_or("").trim();
+    usize::from_str_radix(size, 16)
+        .map_err(|_| WebDriverError::InvalidResponse(format!("Bad chunk size: {}", line)))
+}
+pub(crate) fn cont_length_header(content: &str) -> Vec<String> {
+    vec![
+        String::from("Content-Type: application/json; charset=utf-8"),
+        format!("Content-Length: {}", content.len()),
+    ]
+}
+pub(crate) fn body_for_find_element<S: AsRef<str>>(loc_strategy: &LocatorStrategy<S>) -> String {
+    serde_json::json!(loc_strategy).to_string()
+}
+///Extracts the (web element identifier, element id) pair from a W3C element reference
+pub(crate) fn element_reference(
+    value: serde_json::Value,
+) -> Result<(String, String), WebDriverError> {
+    let map: HashMap<String, String> = serde_json::from_value(value)?;
+    map.into_iter().next().ok_or_else(|| {
+        WebDriverError::InvalidResponse(String::from("No element reference in the response"))
+    })
 }
 pub(crate) fn from_str_vec_to_str(vec: Vec<&str>) -> String {
     if vec.len() == 0 {
@@ -273,22 +391,46 @@ pub(crate) fn from_str_vec_to_str(vec: Vec<&str>) -> String {
 
 //TESTS FOR PRIVATE FUNCTIONS
 #[test]
-fn resp_body_extraction() {
-    let response = "I am the metadata\r\n\r\n{\"hi\":\"there\"}".to_string();
-    assert_eq!(
-        "{\"hi\":\"there\"}".to_string(),
-        resp_body(response).unwrap()
-    );
+fn content_length_response() {
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 14\r\n\r\n{\"hi\":\"there\"}HTTP/1.1";
+    let mut reader = &raw[..];
+    let resp = read_response(&mut reader).unwrap();
+    assert_eq!(resp.status, 200);
+    assert_eq!(resp.header("content-type"), Some("application/json"));
+    assert_eq!(resp.body, b"{\"hi\":\"there\"}");
+    assert_eq!(reader, b"HTTP/1.1");
+}
+#[test]
+fn chunked_response() {
+    let raw = b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n5;ext=1\r\n{\"val\r\n9\r\nue\":null}\r\n0\r\nX-Trailer: yes\r\n\r\n";
+    let mut reader = &raw[..];
+    let resp = read_response(&mut reader).unwrap();
+    assert_eq!(resp.status, 404);
+    assert_eq!(resp.body, b"{\"value\":null}");
+    assert!(reader.is_empty());
+}
+#[test]
+fn close_delimited_response() {
+    let raw = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.0 200 OK\r\nServer: test\r\n\r\nabc";
+    let resp = read_response(&mut &raw[..]).unwrap();
+    assert_eq!(resp.status, 200);
+    assert_eq!(resp.body, b"abc");
+}
+#[test]
+fn truncated_response() {
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc";
+    let resp = read_response(&mut &raw[..]);
+    assert!(matches!(resp, Err(WebDriverError::Transport(_))));
 }
 #[test]
 fn delete_req_creation() {
     let del_req =
-        "DELETE /hello/you HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 130\r\n\r\n\r\n"
-            .to_string();
+        "DELETE /hello/you HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 130\r\n\r\n".to_string();
     assert_eq!(
         del_req,
         create_req(
             Method::DELETE,
+            "127.0.0.1",
             "hello/you",
             vec!["Content-Length: 130".to_string()],
             "{dsd}"
@@ -298,11 +440,12 @@ fn delete_req_creation() {
 #[test]
 fn get_req_creation() {
     let del_req =
-        "GET /hello/you HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 130\r\n\r\n\r\n".to_string();
+        "GET /hello/you HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 130\r\n\r\n".to_string();
     assert_eq!(
         del_req,
         create_req(
             Method::GET,
+            "127.0.0.1",
             "hello/you",
             vec!["Content-Length: 130".to_string()],
             "{dsd}"
@@ -310,15 +453,79 @@ fn get_req_creation() {
     );
 }
 #[test]
+fn post_req_creation() {
+    let body = r#"{"url":"https://приклад.рф"}"#;
+    let post_req = format!(
+        "POST /hello/you HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    assert_eq!(
+        post_req,
+        create_req(
+            Method::POST,
+            "127.0.0.1",
+            "hello/you",
+            cont_length_header(body),
+            body
+        )
+    );
+}
+#[test]
+fn value_extraction() {
+    let resp = r#"{
+       "value": {
+       "dftg43rert34tert-34trte-243f-4":
+       {
+        "id": 333
+        }
+      }
+     }"#;
+    let res = parse_response(200, resp.as_bytes()).unwrap();
+    assert_eq!(
+        res.to_string(),
+        "{\"dftg43rert34tert-34trte-243f-4\":{\"id\":333}}"
+    );
+}
+#[test]
+fn error_extraction() {
+    let resp = r#"{"value":{"error":"no such element","message":"Unable to locate element","stacktrace":"at line 1"}}"#;
+    match parse_response(404, resp.as_bytes()) {
+        Err(WebDriverError::NoSuchElement(details)) => {
+            assert_eq!(details.get_message(), "Unable to locate element");
+            assert_eq!(details.get_stacktrace(), "at line 1");
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+#[test]
+fn error_without_w3c_payload() {
+    let resp = parse_response(404, b"<html>Not Found</html>");
+    match resp {
+        Err(WebDriverError::InvalidResponse(message)) => assert!(message.contains("404")),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    let value = parse_response(200, br#"{"value":{"error":"is a script result"}}"#).unwrap();
+    assert_eq!(value["error"], "is a script result");
+}
+#[test]
 fn ge_t_status() {
-    let response = send_request(
-        "127.0.0.1",
-        "4444",
-        Method::GET,
-        "wd/hub/status",
-        vec![],
-        "",
-    )
-    .unwrap();
-    assert!(response.contains("Server is running"));
+    let response = TcpTransport::new("127.0.0.1", "4444")
+        .send(Method::GET, "wd/hub/status", "")
+        .unwrap();
+    assert!(String::from_utf8_lossy(&response.body).contains("Server is running"));
+}
+#[test]
+fn find_body_escaping() {
+    let selector = String::from(r#"a[title="say \"hi\""]"#);
+    let body = body_for_find_element(&LocatorStrategy::CSS(selector.as_str()));
+    let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(parsed["using"], "css selector");
+    assert_eq!(parsed["value"], selector.as_str());
+    let owned = LocatorStrategy::XPATH(format!("//*[@id='{}']", 7));
+    assert_eq!(owned.to_owned_locator(), owned);
+    assert_eq!(
+        body_for_find_element(&owned),
+        r#"{"using":"xpath","value":"//*[@id='7']"}"#
+    );
 }
@@ -0,0 +1,368 @@
+#[cfg(feature = "async")]
+use super::asyncbrowser::*;
+#[cfg(feature = "async")]
+use super::asyncelement::*;
+use super::browser::*;
+use super::element::*;
+use super::error::*;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder, RgbaImage};
+
+///The format the screenshots are encoded to by encode_image and Browser::save_screenshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    Png,
+    ///The quality from 1 (the smallest file) to 100 (the best image), the other values are clamped.
+    /// JPEG has no transparency, so the alpha channel is dropped.
+    Jpeg(u8),
+    ///Lossless WebP, the image crate can not encode the lossy one
+    WebP,
+}
+impl ScreenshotFormat {
+    ///The usual file extension without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "png",
+            ScreenshotFormat::Jpeg(_) => "jpg",
+            ScreenshotFormat::WebP => "webp",
+        }
+    }
+}
+
+const PAGE_METRICS_SCRIPT: &str = "var d = document.documentElement, b = document.body || d; return [Math.max(d.scrollWidth, b.scrollWidth), Math.max(d.scrollHeight, b.scrollHeight), d.clientWidth, d.clientHeight, window.innerWidth, window.pageXOffset, window.pageYOffset];";
+const SCROLL_SCRIPT: &str =
+    "window.scrollTo(arguments[0], arguments[1]); return [window.pageXOffset, window.pageYOffset];";
+
+///Decodes the PNG returned by the screenshot commands
+pub fn decode_png(png: &[u8]) -> Result<RgbaImage, WebDriverError> {
+    let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+        .map_err(WebDriverError::Image)?;
+    Ok(image.into_rgba8())
+}
+
+///Encodes the image in memory, e.g. to attach it to a report
+pub fn encode_image(
+    image: &RgbaImage,
+    format: ScreenshotFormat,
+) -> Result<Vec<u8>, WebDriverError> {
+    let mut encoded = vec![];
+    let (width, height) = image.dimensions();
+    let result = match format {
+        ScreenshotFormat::Png => PngEncoder::new(&mut encoded).write_image(
+            image.as_raw(),
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+        ),
+        ScreenshotFormat::Jpeg(quality) => {
+            let rgb = image::DynamicImage::ImageRgba8(image.clone()).into_rgb8();
+            JpegEncoder::new_with_quality(&mut encoded, quality.clamp(1, 100)).write_image(
+                rgb.as_raw(),
+                width,
+                height,
+                ExtendedColorType::Rgb8,
+            )
+        }
+        ScreenshotFormat::WebP => WebPEncoder::new_lossless(&mut encoded).write_image(
+            image.as_raw(),
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+        ),
+    };
+    result.map_err(WebDriverError::Image)?;
+    Ok(encoded)
+}
+
+///Encodes the image and writes it to the path, whatever its extension is
+pub fn save_image(
+    image: &RgbaImage,
+    path: &str,
+    format: ScreenshotFormat,
+) -> Result<(), WebDriverError> {
+    let encoded = encode_image(image, format)?;
+    std::fs::write(path, encoded).map_err(WebDriverError::Io)
+}
+
+///The screenshots as the decoded images, available with the "image" feature
+impl Browser {
+    ///The screenshot of the viewport
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use selenium_webdriver::*;
+    /// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+    /// br.open("https://vk.com").unwrap();
+    /// let screen = br.get_screenshot_image().unwrap();
+    /// let header = image::imageops::crop_imm(&screen, 0, 0, screen.width(), 100).to_image();
+    /// save_image(&header, "header.jpg", ScreenshotFormat::Jpeg(80)).unwrap();
+    /// ```
+    pub fn get_screenshot_image(&self) -> Result<RgbaImage, WebDriverError> {
+        decode_png(&self.get_screenshot_png()?)
+    }
+    pub fn get_element_screenshot_image(
+        &self,
+        elem: &Element,
+    ) -> Result<RgbaImage, WebDriverError> {
+        decode_png(&self.get_element_screenshot_png(elem)?)
+    }
+    ///Saves the screenshot of the viewport in the format, e.g. ScreenshotFormat::Jpeg(75)
+    pub fn save_screenshot(
+        &self,
+        path: &str,
+        format: ScreenshotFormat,
+    ) -> Result<(), WebDriverError> {
+        save_image(&self.get_screenshot_image()?, path, format)
+    }
+    ///The image of the whole document, see get_full_page_screenshot_png
+    pub fn get_full_page_screenshot_image(&self) -> Result<RgbaImage, WebDriverError> {
+        match self.native_full_page_screenshot()? {
+            Some(png) => decode_png(&png),
+            None => self.stitch_full_page_screenshot(),
+        }
+    }
+    //Scrolls through the document and puts the viewport screenshots together,
+    //then scrolls back to where the page was, also when the stitching failed
+    pub(crate) fn stitch_full_page_screenshot(&self) -> Result<RgbaImage, WebDriverError> {
+        let (page_width, page_height, view_width, view_height, inner_width, start_x, start_y): (
+            f64,
+            f64,
+            f64,
+            f64,
+            f64,
+            f64,
+            f64,
+        ) = self.execute(PAGE_METRICS_SCRIPT, ())?;
+        let (view_width, view_height) = (view_width.max(1.0), view_height.max(1.0));
+        let stitch = || -> Result<Option<RgbaImage>, WebDriverError> {
+            let mut page: Option<RgbaImage> = None;
+            let mut y = 0.0;
+            while y < page_height {
+                let mut x = 0.0;
+                while x < page_width {
+                    let (scroll_x, scroll_y): (f64, f64) = self.execute(SCROLL_SCRIPT, (x, y))?;
+                    let screen = self.get_screenshot_image()?;
+                    let ratio = screen.width() as f64 / inner_width.max(1.0);
+                    let scaled = |value: f64| (value * ratio).round() as u32;
+                    let page = page.get_or_insert_with(|| {
+                        RgbaImage::new(scaled(page_width), scaled(page_height))
+                    });
+                    let viewport = image::imageops::crop_imm(
+                        &screen,
+                        0,
+                        0,
+                        scaled(view_width).min(screen.width()),
+                        scaled(view_height).min(screen.height()),
+                    )
+                    .to_image();
+                    image::imageops::replace(
+                        page,
+                        &viewport,
+                        scaled(scroll_x) as i64,
+                        scaled(scroll_y) as i64,
+                    );
+                    x += view_width;
+                }
+                y += view_height;
+            }
+            Ok(page)
+        };
+        let page = stitch();
+        let restored = self.execute::<(f64, f64), _>(SCROLL_SCRIPT, (start_x, start_y));
+        //The stitching error is the one worth reporting, not the failed scroll back
+        let page = page?;
+        restored?;
+        match page {
+            Some(page) => Ok(page),
+            None => self.get_screenshot_image(),
+        }
+    }
+    pub fn save_element_screenshot(
+        &self,
+        elem: &Element,
+        path: &str,
+        format: ScreenshotFormat,
+    ) -> Result<(), WebDriverError> {
+        save_image(&self.get_element_screenshot_image(elem)?, path, format)
+    }
+}
+
+///The same decoded screenshots for the AsyncBrowser, the full page ones are available in the Browser only
+#[cfg(feature = "async")]
+impl AsyncBrowser {
+    pub async fn get_screenshot_image(&self) -> Result<RgbaImage, WebDriverError> {
+        decode_png(&self.get_screenshot_png().await?)
+    }
+    pub async fn get_element_screenshot_image(
+        &self,
+        elem: &AsyncElement,
+    ) -> Result<RgbaImage, WebDriverError> {
+        decode_png(&self.get_element_screenshot_png(elem).await?)
+    }
+    ///See Browser::save_screenshot
+    pub async fn save_screenshot(
+        &self,
+        path: &str,
+        format: ScreenshotFormat,
+    ) -> Result<(), WebDriverError> {
+        let encoded = encode_image(&self.get_screenshot_image().await?, format)?;
+        tokio::fs::write(path, encoded)
+            .await
+            .map_err(WebDriverError::Io)
+    }
+    pub async fn save_element_screenshot(
+        &self,
+        elem: &AsyncElement,
+        path: &str,
+        format: ScreenshotFormat,
+    ) -> Result<(), WebDriverError> {
+        let encoded = encode_image(&self.get_element_screenshot_image(elem).await?, format)?;
+        tokio::fs::write(path, encoded)
+            .await
+            .map_err(WebDriverError::Io)
+    }
+}
+
+mod screenshot_tests {
+    use super::*;
+    use crate::reqs::*;
+    use crate::transport::*;
+    fn sample() -> RgbaImage {
+        RgbaImage::from_fn(4, 3, |x, y| {
+            image::Rgba([x as u8 * 60, y as u8 * 80, 10, 255])
+        })
+    }
+    #[test]
+    fn encodes_formats() {
+        let image = sample();
+        let png = encode_image(&image, ScreenshotFormat::Png).unwrap();
+        assert_eq!(decode_png(&png).unwrap(), image);
+        let webp = encode_image(&image, ScreenshotFormat::WebP).unwrap();
+        let decoded = image::load_from_memory(&webp).unwrap().into_rgba8();
+        assert_eq!(decoded, image);
+        let best = encode_image(&image, ScreenshotFormat::Jpeg(100)).unwrap();
+        let worst = encode_image(&image, ScreenshotFormat::Jpeg(0)).unwrap();
+        assert_eq!(&best[..2], &[0xFF, 0xD8]);
+        assert!(worst.len() < best.len());
+        let decoded = image::load_from_memory(&best).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (4, 3));
+        let err = decode_png(&webp).unwrap_err();
+        assert!(matches!(err, WebDriverError::Image(_)));
+    }
+    #[test]
+    fn decodes_screenshots() {
+        let png = encode_image(&sample(), ScreenshotFormat::Png).unwrap();
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::GET,
+            "wd/hub/session/s1/screenshot",
+            &format!(r#"{{"value":"{}"}}"#, base64::encode(&png)),
+        );
+        assert_eq!(br.get_screenshot_image().unwrap(), sample());
+        assert!(mock.is_done());
+    }
+    #[test]
+    fn stitches_full_page() {
+        let viewport = |value: u8| {
+            let png = encode_image(
+                &RgbaImage::from_pixel(4, 4, image::Rgba([value, value, value, 255])),
+                ScreenshotFormat::Png,
+            )
+            .unwrap();
+            format!(r#"{{"value":"{}"}}"#, base64::encode(&png))
+        };
+        let (mock, br) = mock_browser("firefox");
+        mock.expect_status(
+            Method::GET,
+            "wd/hub/session/s1/moz/screenshot/full",
+            404,
+            r#"{"value":{"error":"unknown command","message":"grid","stacktrace":""}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[2,3,2,2,2,0,1]}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[0,0]}"#,
+        )
+        .expect(Method::GET, "wd/hub/session/s1/screenshot", &viewport(10))
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[0,1]}"#,
+        )
+        .expect(Method::GET, "wd/hub/session/s1/screenshot", &viewport(20))
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[0,1]}"#,
+        );
+        let page = br.get_full_page_screenshot_image().unwrap();
+        assert!(mock.is_done());
+        //The device pixel ratio is 2, the second viewport is scrolled by 1 css pixel only
+        assert_eq!(page.dimensions(), (4, 6));
+        assert_eq!(page.get_pixel(3, 1), &image::Rgba([10, 10, 10, 255]));
+        assert_eq!(page.get_pixel(3, 2), &image::Rgba([20, 20, 20, 255]));
+        assert_eq!(page.get_pixel(0, 5), &image::Rgba([20, 20, 20, 255]));
+        let requests = mock.requests();
+        assert!(requests[5].get_body().contains(r#""args":[0.0,2.0]"#));
+        assert!(requests[7].get_body().contains(r#""args":[0.0,1.0]"#));
+    }
+    #[test]
+    fn stitching_restores_scroll_on_error() {
+        let (mock, br) = mock_browser("safari");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[2,3,2,2,2,0,1]}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[0,0]}"#,
+        )
+        .expect_status(
+            Method::GET,
+            "wd/hub/session/s1/screenshot",
+            500,
+            r#"{"value":{"error":"unable to capture screen","message":"","stacktrace":""}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[0,1]}"#,
+        );
+        let err = br.get_full_page_screenshot_image().unwrap_err();
+        assert!(matches!(err, WebDriverError::UnableToCaptureScreen(_)));
+        assert!(mock.is_done());
+        assert!(mock.requests()[4]
+            .get_body()
+            .contains(r#""args":[0.0,1.0]"#));
+    }
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_screenshots() {
+        let png = encode_image(&sample(), ScreenshotFormat::Png).unwrap();
+        let screenshot = format!(r#"{{"value":"{}"}}"#, base64::encode(&png));
+        let (mock, br) = mock_async_browser("chrome").await;
+        mock.expect(Method::GET, "wd/hub/session/s1/screenshot", &screenshot)
+            .expect(Method::GET, "wd/hub/session/s1/screenshot", &screenshot);
+        assert_eq!(br.get_screenshot_image().await.unwrap(), sample());
+        let path = std::env::temp_dir().join(format!(
+            "selenium_webdriver_async_screenshot_{}.webp",
+            std::process::id()
+        ));
+        br.save_screenshot(path.to_str().unwrap(), ScreenshotFormat::WebP)
+            .await
+            .unwrap();
+        let saved = image::open(&path).unwrap().into_rgba8();
+        assert_eq!(saved, sample());
+        assert!(mock.is_done());
+        std::fs::remove_file(path).unwrap();
+    }
+}
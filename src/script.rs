@@ -0,0 +1,300 @@
+use super::element::*;
+use super::error::*;
+use super::shadowroot::*;
+use super::transport::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+pub(crate) const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecf";
+pub(crate) const SHADOW_ROOT_KEY: &str = "shadow-6066-11e4-a52e-4f735466cecf";
+
+///An argument of Browser::execute_script, e.g. ScriptArg::from(&element) or ScriptArg::from(5)
+///
+/// The elements are passed as the W3C web element references, so the script gets the DOM nodes.
+#[derive(Debug, Clone)]
+pub enum ScriptArg<'a> {
+    Element(&'a Element),
+    Json(Value),
+}
+impl Serialize for ScriptArg<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ScriptArg::Element(element) => element.serialize(serializer),
+            ScriptArg::Json(value) => value.serialize(serializer),
+        }
+    }
+}
+impl<'a> From<&'a Element> for ScriptArg<'a> {
+    fn from(element: &'a Element) -> Self {
+        ScriptArg::Element(element)
+    }
+}
+impl From<Value> for ScriptArg<'_> {
+    fn from(value: Value) -> Self {
+        ScriptArg::Json(value)
+    }
+}
+impl From<&str> for ScriptArg<'_> {
+    fn from(value: &str) -> Self {
+        ScriptArg::Json(Value::from(value))
+    }
+}
+impl From<String> for ScriptArg<'_> {
+    fn from(value: String) -> Self {
+        ScriptArg::Json(Value::from(value))
+    }
+}
+impl From<bool> for ScriptArg<'_> {
+    fn from(value: bool) -> Self {
+        ScriptArg::Json(Value::from(value))
+    }
+}
+impl From<i64> for ScriptArg<'_> {
+    fn from(value: i64) -> Self {
+        ScriptArg::Json(Value::from(value))
+    }
+}
+impl From<f64> for ScriptArg<'_> {
+    fn from(value: f64) -> Self {
+        ScriptArg::Json(Value::from(value))
+    }
+}
+
+//The body of the execute commands. The args serialized to an array are passed as they are,
+//null (e.g. ()) means no args and any other value is passed as the only arg
+pub(crate) fn script_body<A: Serialize>(script: &str, args: A) -> Result<String, WebDriverError> {
+    let args = match serde_json::to_value(args)? {
+        Value::Array(args) => args,
+        Value::Null => vec![],
+        arg => vec![arg],
+    };
+    Ok(serde_json::json!({ "script": script, "args": args }).to_string())
+}
+
+///The value returned by Browser::execute_script
+///
+/// Mirrors the json value, except that the W3C element and shadow root references
+/// (e.g. returned by document.querySelector) become the Element and ShadowRoot.
+#[derive(Debug)]
+pub enum ScriptValue {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<ScriptValue>),
+    Object(Map<String, Value>),
+    Element(Element),
+    ShadowRoot(ShadowRoot),
+}
+impl ScriptValue {
+    pub(crate) fn from_json(
+        value: Value,
+        transport: Arc<dyn Transport>,
+        session_url: &str,
+    ) -> Result<ScriptValue, WebDriverError> {
+        Ok(match value {
+            Value::Null => ScriptValue::Null,
+            Value::Bool(b) => ScriptValue::Bool(b),
+            Value::Number(n) => ScriptValue::Number(n),
+            Value::String(s) => ScriptValue::String(s),
+            Value::Array(values) => ScriptValue::Array(
+                values
+                    .into_iter()
+                    .map(|v| ScriptValue::from_json(v, transport.clone(), session_url))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(map) if map.len() == 1 && map.contains_key(ELEMENT_KEY) => {
+                ScriptValue::Element(Element::from_reference(
+                    transport,
+                    session_url,
+                    Value::Object(map),
+                )?)
+            }
+            Value::Object(map) if map.len() == 1 && map.contains_key(SHADOW_ROOT_KEY) => {
+                let id = match &map[SHADOW_ROOT_KEY] {
+                    Value::String(id) => id.clone(),
+                    _ => {
+                        return Err(WebDriverError::InvalidResponse(String::from(
+                            "The shadow root reference is not a string",
+                        )))
+                    }
+                };
+                ScriptValue::ShadowRoot(ShadowRoot::new(transport, session_url, id))
+            }
+            Value::Object(map) => ScriptValue::Object(map),
+        })
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self, ScriptValue::Null)
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ScriptValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ScriptValue::Number(n) => n.as_f64(),
+            _ => None,
+        }
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ScriptValue::Number(n) => n.as_i64(),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ScriptValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&Vec<ScriptValue>> {
+        match self {
+            ScriptValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            ScriptValue::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+    pub fn into_element(self) -> Option<Element> {
+        match self {
+            ScriptValue::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+    ///Returns the elements if the value is an array of elements only (e.g. a NodeList)
+    pub fn into_elements(self) -> Option<Vec<Element>> {
+        match self {
+            ScriptValue::Array(values) => values.into_iter().map(|v| v.into_element()).collect(),
+            _ => None,
+        }
+    }
+    pub fn into_shadow_root(self) -> Option<ShadowRoot> {
+        match self {
+            ScriptValue::ShadowRoot(root) => Some(root),
+            _ => None,
+        }
+    }
+}
+
+mod script_tests {
+    use super::*;
+    use crate::browser::*;
+    use crate::reqs::*;
+    #[test]
+    fn passes_and_returns_elements() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"e2"},{"shadow-6066-11e4-a52e-4f735466cecf":"r1"},{"a":1},"x",null]}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/async",
+            r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"e3"}]}"#,
+        );
+        let el = br.find_element(LocatorStrategy::CSS("div")).unwrap();
+        let script = "return [arguments[0].nextSibling, \"x\"]";
+        let value = br
+            .execute_script(script, &[ScriptArg::from(&el), ScriptArg::from(2)])
+            .unwrap();
+        let values = value.as_array().unwrap();
+        assert_eq!(values[0].as_element().unwrap().element_id(), "e2");
+        assert!(matches!(&values[1], ScriptValue::ShadowRoot(r) if r.get_id() == "r1"));
+        assert!(matches!(&values[2], ScriptValue::Object(m) if m["a"] == 1));
+        assert_eq!(values[3].as_str(), Some("x"));
+        assert!(values[4].is_null());
+        assert!(value.into_elements().is_none());
+        let found = br
+            .execute_async_script("arguments[0]([document.body])", &[])
+            .unwrap()
+            .into_elements()
+            .unwrap();
+        assert_eq!(found[0].element_id(), "e3");
+        assert!(mock.is_done());
+        let body: Value = serde_json::from_str(mock.requests()[2].get_body()).unwrap();
+        assert_eq!(body["script"], script);
+        assert_eq!(
+            body["args"],
+            serde_json::json!([{"element-6066-11e4-a52e-4f735466cecf":"e1"}, 2])
+        );
+    }
+    #[test]
+    fn typed_execution() {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":{"tag":"DIV","width":10.5}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/async",
+            r#"{"value":[1,2]}"#,
+        )
+        .expect_status(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            500,
+            r#"{"value":{"error":"javascript error","message":"ReferenceError: foo is not defined","stacktrace":"at <anonymous>:1:1"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":"text"}"#,
+        );
+        let el = br.find_element(LocatorStrategy::CSS("div")).unwrap();
+        #[derive(serde::Deserialize)]
+        struct Info {
+            tag: String,
+            width: f64,
+        }
+        let script = "return {\n\"tag\": arguments[0].tagName, width: arguments[1]}";
+        let info: Info = br.execute(script, (&el, 10.5, "it's")).unwrap();
+        assert_eq!((info.tag.as_str(), info.width), ("DIV", 10.5));
+        let numbers: Vec<u8> = br.execute_async_as("arguments[1]([1, 2])", 5).unwrap();
+        assert_eq!(numbers, vec![1, 2]);
+        let err = br.execute::<(), _>("foo()", ()).unwrap_err();
+        match err {
+            WebDriverError::JavascriptError(details) => {
+                assert_eq!(details.get_message(), "ReferenceError: foo is not defined");
+                assert_eq!(details.get_stacktrace(), "at <anonymous>:1:1");
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        let err = br.execute::<i32, _>("return 'text'", ()).unwrap_err();
+        assert!(matches!(err, WebDriverError::Json(_)));
+        assert!(mock.is_done());
+        let requests = mock.requests();
+        let body: Value = serde_json::from_str(requests[2].get_body()).unwrap();
+        assert_eq!(body["script"], script);
+        assert_eq!(
+            body["args"],
+            serde_json::json!([{"element-6066-11e4-a52e-4f735466cecf":"e1"}, 10.5, "it's"])
+        );
+        assert_eq!(
+            requests[3].get_body(),
+            r#"{"args":[5],"script":"arguments[1]([1, 2])"}"#
+        );
+        assert_eq!(requests[4].get_body(), r#"{"args":[],"script":"foo()"}"#);
+    }
+}
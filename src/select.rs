@@ -0,0 +1,331 @@
+use super::by::*;
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+
+///The helper for the <select> elements
+///
+/// Selecting a disabled option fails with the InvalidElementState error before any of the matching
+/// options is selected, selecting a missing one with the NoSuchElement error. Deselecting is only possible in the multi-selects,
+/// for the others it returns the UnsupportedOperation error.
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+/// br.open("https://www.w3schools.com/tags/tryhtml_select.asp").unwrap();
+/// let cars = Select::new(br.find_element(By::id("cars")).unwrap()).unwrap();
+/// cars.select_by_visible_text("Opel").unwrap();
+/// let selected = cars.get_first_selected_option().unwrap();
+/// assert_eq!(selected.get_attribute("value").unwrap(), "opel");
+/// ```
+#[derive(Debug)]
+pub struct Select {
+    element: Element,
+    multiple: bool,
+}
+impl Select {
+    ///Returns the InvalidArgument error if the element is not a <select>
+    pub fn new(element: Element) -> Result<Select, WebDriverError> {
+        let tag_name = element.get_tag_name()?;
+        if !tag_name.eq_ignore_ascii_case("select") {
+            return Err(select_error(
+                "invalid argument",
+                format!("The element should be a select, but it is {}", tag_name),
+            ));
+        }
+        let multiple = element.get_attribute("multiple")?;
+        Ok(Select {
+            element,
+            multiple: multiple != "null" && multiple != "false",
+        })
+    }
+    pub fn get_element(&self) -> &Element {
+        &self.element
+    }
+    ///Whether several options may be selected at once
+    pub fn is_multiple(&self) -> bool {
+        self.multiple
+    }
+    pub fn get_options(&self) -> Result<Vec<Element>, WebDriverError> {
+        self.element
+            .find_elements_from_self(LocatorStrategy::TAGNAME("option"))
+    }
+    pub fn get_selected_options(&self) -> Result<Vec<Element>, WebDriverError> {
+        let mut selected = vec![];
+        for option in self.get_options()? {
+            if option.is_selected()? {
+                selected.push(option);
+            }
+        }
+        Ok(selected)
+    }
+    ///The selected option of a single select, the first selected one of a multi-select
+    pub fn get_first_selected_option(&self) -> Result<Element, WebDriverError> {
+        for option in self.get_options()? {
+            if option.is_selected()? {
+                return Ok(option);
+            }
+        }
+        Err(select_error(
+            "no such element",
+            String::from("No option is selected"),
+        ))
+    }
+    ///Selects the options with this text (all of them in a multi-select, the first one otherwise).
+    /// The text is compared after trimming the leading and trailing whitespace.
+    pub fn select_by_visible_text(&self, text: &str) -> Result<(), WebDriverError> {
+        let options = self.options_by_text(text)?;
+        self.select_options(options, &format!("text \"{}\"", text))
+    }
+    ///Selects the options with this value attribute (all of them in a multi-select, the first one otherwise)
+    pub fn select_by_value(&self, value: &str) -> Result<(), WebDriverError> {
+        let options = self.options_by_value(value)?;
+        self.select_options(options, &format!("value \"{}\"", value))
+    }
+    ///Selects the option by its position among the options, starting from 0
+    pub fn select_by_index(&self, index: usize) -> Result<(), WebDriverError> {
+        let options = self.option_by_index(index)?;
+        self.select_options(options, &format!("index {}", index))
+    }
+    pub fn deselect_all(&self) -> Result<(), WebDriverError> {
+        self.check_multiple()?;
+        for option in self.get_options()? {
+            set_selected(&option, false)?;
+        }
+        Ok(())
+    }
+    pub fn deselect_by_visible_text(&self, text: &str) -> Result<(), WebDriverError> {
+        self.check_multiple()?;
+        let options = self.options_by_text(text)?;
+        self.deselect_options(options, &format!("text \"{}\"", text))
+    }
+    pub fn deselect_by_value(&self, value: &str) -> Result<(), WebDriverError> {
+        self.check_multiple()?;
+        let options = self.options_by_value(value)?;
+        self.deselect_options(options, &format!("value \"{}\"", value))
+    }
+    pub fn deselect_by_index(&self, index: usize) -> Result<(), WebDriverError> {
+        self.check_multiple()?;
+        let options = self.option_by_index(index)?;
+        self.deselect_options(options, &format!("index {}", index))
+    }
+    fn options_by_text(&self, text: &str) -> Result<Vec<Element>, WebDriverError> {
+        let mut matching = vec![];
+        for option in self.get_options()? {
+            if option.get_element_text()?.trim() == text.trim() {
+                matching.push(option);
+            }
+        }
+        Ok(matching)
+    }
+    fn options_by_value(&self, value: &str) -> Result<Vec<Element>, WebDriverError> {
+        let css = format!("option[value={}]", By::css_string(value));
+        self.element
+            .find_elements_from_self(LocatorStrategy::CSS(css))
+    }
+    fn option_by_index(&self, index: usize) -> Result<Vec<Element>, WebDriverError> {
+        Ok(self
+            .get_options()?
+            .into_iter()
+            .nth(index)
+            .into_iter()
+            .collect())
+    }
+    fn select_options(&self, options: Vec<Element>, which: &str) -> Result<(), WebDriverError> {
+        if options.is_empty() {
+            return Err(no_such_option(which));
+        }
+        let count = if self.multiple { options.len() } else { 1 };
+        //All the options are checked first, so a multi-select is not left partly selected
+        for option in &options[..count] {
+            if !option.is_enabled()? {
+                return Err(select_error(
+                    "invalid element state",
+                    format!("The option with the {} is disabled", which),
+                ));
+            }
+        }
+        for option in &options[..count] {
+            set_selected(option, true)?;
+        }
+        Ok(())
+    }
+    fn deselect_options(&self, options: Vec<Element>, which: &str) -> Result<(), WebDriverError> {
+        if options.is_empty() {
+            return Err(no_such_option(which));
+        }
+        for option in options {
+            set_selected(&option, false)?;
+        }
+        Ok(())
+    }
+    fn check_multiple(&self) -> Result<(), WebDriverError> {
+        if self.multiple {
+            return Ok(());
+        }
+        Err(select_error(
+            "unsupported operation",
+            String::from("Only the options of a multi-select may be deselected"),
+        ))
+    }
+}
+//Clicking an option toggles it in a multi-select, so the state is checked first
+fn set_selected(option: &Element, selected: bool) -> Result<(), WebDriverError> {
+    if option.is_selected()? != selected {
+        option.click()?;
+    }
+    Ok(())
+}
+fn no_such_option(which: &str) -> WebDriverError {
+    select_error(
+        "no such element",
+        format!("There is no option with the {}", which),
+    )
+}
+fn select_error(error: &str, message: String) -> WebDriverError {
+    WebDriverError::from_details(ErrorDetails {
+        error: String::from(error),
+        message,
+        stacktrace: String::new(),
+        data: None,
+    })
+}
+
+mod select_tests {
+    use super::*;
+    use crate::browser::*;
+    use crate::transport::*;
+    const OPTIONS: &str = r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"o1"},{"element-6066-11e4-a52e-4f735466cecf":"o2"}]}"#;
+    #[cfg(test)]
+    fn mock_select(multiple: &str) -> (MockTransport, Browser, Select) {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"sel"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/sel/name",
+            r#"{"value":"SELECT"}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/sel/attribute/multiple",
+            multiple,
+        );
+        let select = Select::new(br.find_element(By::id("cars")).unwrap()).unwrap();
+        (mock, br, select)
+    }
+    #[test]
+    fn selects_by_text_and_rejects_disabled() {
+        let (mock, _br, select) = mock_select(r#"{"value":null}"#);
+        assert!(!select.is_multiple());
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element/sel/elements",
+            OPTIONS,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o1/text",
+            r#"{"value":"Volvo"}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o2/text",
+            r#"{"value":" Opel "}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o2/enabled",
+            r#"{"value":true}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o2/selected",
+            r#"{"value":false}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/o2/click",
+            r#"{"value":null}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/sel/elements",
+            OPTIONS,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o1/enabled",
+            r#"{"value":false}"#,
+        );
+        select.select_by_visible_text("Opel").unwrap();
+        let err = select.select_by_index(0).unwrap_err();
+        assert!(matches!(err, WebDriverError::InvalidElementState(_)));
+        let err = select.deselect_all().unwrap_err();
+        assert!(matches!(err, WebDriverError::UnsupportedOperation(_)));
+        assert!(mock.is_done());
+    }
+    #[test]
+    fn deselects_by_value_in_multiselect() {
+        let (mock, _br, select) = mock_select(r#"{"value":"true"}"#);
+        assert!(select.is_multiple());
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element/sel/elements",
+            r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"o2"}]}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o2/selected",
+            r#"{"value":true}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/o2/click",
+            r#"{"value":null}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/sel/elements",
+            r#"{"value":[]}"#,
+        );
+        select.deselect_by_value("o\"pel").unwrap();
+        let err = select.select_by_value("saab").unwrap_err();
+        assert!(matches!(err, WebDriverError::NoSuchElement(_)));
+        assert!(mock.is_done());
+        assert_eq!(
+            mock.requests()[4].get_body(),
+            r#"{"using":"css selector","value":"option[value=\"o\\\"pel\"]"}"#
+        );
+    }
+    #[test]
+    fn disabled_option_selects_nothing_in_multiselect() {
+        let (mock, _br, select) = mock_select(r#"{"value":"true"}"#);
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element/sel/elements",
+            OPTIONS,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o1/enabled",
+            r#"{"value":true}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/o2/enabled",
+            r#"{"value":false}"#,
+        );
+        let err = select.select_by_value("same").unwrap_err();
+        assert!(matches!(err, WebDriverError::InvalidElementState(_)));
+        assert!(mock.is_done());
+        assert!(!mock
+            .requests()
+            .iter()
+            .any(|r| r.get_path().ends_with("/click")));
+    }
+}
@@ -0,0 +1,137 @@
+use super::error::*;
+use std::str::FromStr;
+
+///The base url of the WebDriver endpoint: scheme, host, port and an optional path prefix
+///
+/// The selenium server 3 listens on "http://127.0.0.1:4444/wd/hub", which is the default.
+/// Selenium 4 Grid and the bare drivers (chromedriver, geckodriver, safaridriver) serve
+/// the commands from the root, so the prefix should be left empty for them.
+/// Only the plain http scheme is supported at the moment.
+///
+/// # Examples
+/// ```
+/// # use selenium_webdriver::*;
+/// let grid: ServerUrl = "http://10.0.0.5:4444".parse().unwrap();
+/// assert_eq!(grid.get_prefix(), "");
+/// let mut chromedriver = ServerUrl::new("localhost", 9515);
+/// chromedriver.set_prefix("");
+/// assert_eq!(chromedriver.to_string(), "http://localhost:9515");
+/// assert_eq!(ServerUrl::default().to_string(), "http://127.0.0.1:4444/wd/hub");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerUrl {
+    scheme: String,
+    host: String,
+    port: u16,
+    prefix: String,
+}
+impl ServerUrl {
+    ///Creates the http url with the default "wd/hub" prefix
+    pub fn new(host: &str, port: u16) -> Self {
+        ServerUrl {
+            scheme: String::from("http"),
+            host: String::from(host),
+            port,
+            prefix: String::from("wd/hub"),
+        }
+    }
+    ///Parses the url like "http://localhost:4444/wd/hub". The port defaults to 80 if omitted.
+    pub fn parse(url: &str) -> Result<Self, WebDriverError> {
+        let invalid = |reason: &str| WebDriverError::InvalidUrl(format!("{}: {}", url, reason));
+        let (scheme, rest) = match url.split_once("://") {
+            Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+            None => return Err(invalid("the scheme is missing")),
+        };
+        if scheme != "http" {
+            return Err(invalid("only the http scheme is supported"));
+        }
+        let (authority, prefix) = match rest.split_once('/') {
+            Some((authority, prefix)) => (authority, prefix),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => {
+                let port = port.parse().map_err(|_| invalid("bad port"))?;
+                (host, port)
+            }
+            _ => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid("the host is missing"));
+        }
+        let mut server_url = ServerUrl::new(host, port);
+        server_url.scheme = scheme;
+        server_url.set_prefix(prefix);
+        Ok(server_url)
+    }
+    ///Sets the path prefix of all the commands, e.g. "wd/hub". The slashes around it are optional.
+    pub fn set_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.prefix = String::from(prefix.trim_matches('/'));
+        self
+    }
+    pub fn get_scheme(&self) -> &str {
+        &self.scheme
+    }
+    pub fn get_host(&self) -> &str {
+        &self.host
+    }
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+    ///The path of the endpoint relative to the server root, without the leading slash
+    pub(crate) fn endpoint(&self, path: &str) -> String {
+        if self.prefix.is_empty() {
+            String::from(path)
+        } else {
+            format!("{}/{}", self.prefix, path)
+        }
+    }
+}
+impl Default for ServerUrl {
+    fn default() -> Self {
+        ServerUrl::new("127.0.0.1", 4444)
+    }
+}
+impl FromStr for ServerUrl {
+    type Err = WebDriverError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ServerUrl::parse(s)
+    }
+}
+impl std::fmt::Display for ServerUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}:{}", self.scheme, self.host, self.port)?;
+        if !self.prefix.is_empty() {
+            write!(f, "/{}", self.prefix)?;
+        }
+        Ok(())
+    }
+}
+
+mod server_url_tests {
+    use super::*;
+    #[test]
+    fn parse_full_url() {
+        let url = ServerUrl::parse("http://grid.local:4444/wd/hub/").unwrap();
+        assert_eq!(url.get_host(), "grid.local");
+        assert_eq!(url.get_port(), 4444);
+        assert_eq!(url.endpoint("session"), "wd/hub/session");
+    }
+    #[test]
+    fn parse_root_url() {
+        let url = ServerUrl::parse("http://localhost:9515").unwrap();
+        assert_eq!(url.endpoint("session"), "session");
+        let url = ServerUrl::parse("http://localhost").unwrap();
+        assert_eq!(url.get_port(), 80);
+    }
+    #[test]
+    fn reject_bad_urls() {
+        assert!(ServerUrl::parse("localhost:4444").is_err());
+        assert!(ServerUrl::parse("https://localhost:4444").is_err());
+        assert!(ServerUrl::parse("http://localhost:port").is_err());
+        assert!(ServerUrl::parse("http://:4444").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+use super::transport::*;
+use std::sync::Arc;
+
+///The shadow root of a web component, returned by Element::shadow_root
+///
+/// The elements inside the shadow tree can only be found through it.
+/// The drivers do not support XPATH inside the shadow trees, use the other strategies.
+/// If the host element is removed
+/// or the page is reloaded, the methods return the DetachedShadowRoot error
+/// and the shadow root has to be got again.
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+/// br.open("chrome://downloads").unwrap();
+/// let manager = br.find_element(LocatorStrategy::TAGNAME("downloads-manager")).unwrap();
+/// let root = manager.shadow_root().unwrap();
+/// let toolbar = root.find_element(By::id("toolbar")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ShadowRoot {
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) shadow_id: String,
+    pub(crate) shadow_url: String,
+    session_url: String,
+}
+impl ShadowRoot {
+    pub(crate) fn new(transport: Arc<dyn Transport>, session_url: &str, id: String) -> ShadowRoot {
+        ShadowRoot {
+            transport,
+            shadow_url: format!("{}/shadow/{}", session_url, id),
+            session_url: String::from(session_url),
+            shadow_id: id,
+        }
+    }
+    ///Returns the first element in the shadow tree which is found using the locator.
+    pub fn find_element<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Element, WebDriverError> {
+        let url = format!("{}/element", self.shadow_url);
+        let body = body_for_find_element(&locator);
+        let resp = send_command(&*self.transport, Method::POST, &url, &body)?;
+        Element::from_reference(self.transport.clone(), &self.session_url, resp)
+    }
+    ///Returns all elements in the shadow tree which are found using the locator.
+    pub fn find_elements<S: AsRef<str>>(
+        &self,
+        locator: LocatorStrategy<S>,
+    ) -> Result<Vec<Element>, WebDriverError> {
+        let url = format!("{}/elements", self.shadow_url);
+        let body = body_for_find_element(&locator);
+        let resp = send_command(&*self.transport, Method::POST, &url, &body)?;
+        let refs: Vec<serde_json::Value> = serde_json::from_value(resp)?;
+        refs.into_iter()
+            .map(|r| Element::from_reference(self.transport.clone(), &self.session_url, r))
+            .collect()
+    }
+    pub fn get_id(&self) -> &str {
+        &self.shadow_id
+    }
+}
+impl Element {
+    ///Returns the shadow root attached to the element,
+    /// the NoSuchShadowRoot error if there is none (or it is a closed one)
+    pub fn shadow_root(&self) -> Result<ShadowRoot, WebDriverError> {
+        let url = format!("{}/shadow", self.element_url());
+        let resp = send_command(&*self.transport, Method::GET, &url, "")?;
+        let (_, id) = element_reference(resp)?;
+        Ok(ShadowRoot::new(
+            self.transport.clone(),
+            self.session_url(),
+            id,
+        ))
+    }
+}
+
+mod shadow_root_tests {
+    use super::*;
+    use crate::browser::*;
+    use crate::by::*;
+    #[test]
+    fn finds_inside_shadow_tree() {
+        let (mock, br) = mock_browser("chrome");
+        let detached = r#"{"value":{"error":"detached shadow root","message":"","stacktrace":""}}"#;
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"host"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/host/shadow",
+            r#"{"value":{"shadow-6066-11e4-a52e-4f735466cecf":"r1"}}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/shadow/r1/elements",
+            r#"{"value":[{"element-6066-11e4-a52e-4f735466cecf":"in1"}]}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/element/in1/text",
+            r#"{"value":"Inner"}"#,
+        )
+        .expect_status(
+            Method::POST,
+            "wd/hub/session/s1/shadow/r1/element",
+            404,
+            detached,
+        );
+        let host = br.find_element(LocatorStrategy::TAGNAME("my-app")).unwrap();
+        let root = host.shadow_root().unwrap();
+        assert_eq!(root.get_id(), "r1");
+        let inner = root.find_elements(By::class_name("title")).unwrap();
+        assert_eq!(inner[0].get_element_text().unwrap(), "Inner");
+        let err = root.find_element(By::id("gone")).unwrap_err();
+        assert!(matches!(err, WebDriverError::DetachedShadowRoot(_)));
+        assert!(mock.is_done());
+        let requests = mock.requests();
+        assert_eq!(
+            requests[3].get_body(),
+            r#"{"using":"css selector","value":".title"}"#
+        );
+    }
+    #[test]
+    fn keeps_the_session_url() {
+        let mock = MockTransport::new();
+        mock.expect(
+            Method::POST,
+            "grid/shadow/session/s1/shadow/r1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"in1"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "grid/shadow/session/s1/element/in1/text",
+            r#"{"value":"Inner"}"#,
+        );
+        let root = ShadowRoot::new(
+            Arc::new(mock.clone()),
+            "grid/shadow/session/s1",
+            String::from("r1"),
+        );
+        let inner = root.find_element(By::id("title")).unwrap();
+        assert_eq!(inner.get_element_text().unwrap(), "Inner");
+        assert!(mock.is_done());
+    }
+}
@@ -0,0 +1,502 @@
+#[cfg(feature = "async")]
+use super::asyncbrowser::*;
+use super::browser::*;
+use super::capabilities::*;
+use super::error::*;
+use super::reqs::*;
+use super::wirelog::*;
+use std::collections::VecDeque;
+use std::io::{BufReader, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+///The channel the Browser and its Elements use to talk to the WebDriver server
+///
+/// The path passed to send() has no leading slash, e.g. "wd/hub/session/{id}/url".
+/// The default implementation is TcpTransport, MockTransport allows to test the code
+/// which uses the Browser without the selenium server.
+pub trait Transport: Send + Sync + std::fmt::Debug {
+    fn send(&self, method: Method, path: &str, body: &str) -> Result<HttpResponse, WebDriverError>;
+    ///Whether the server runs on this machine, so the browser can read the local files.
+    /// False by default, which makes Element::upload_file send the files to the server
+    /// unless the Browser::set_local_file_detector is enabled.
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+///Sends the requests over HTTP/1.1 keep-alive connections to the server
+///
+/// Up to max_idle_connections (4 by default) idle connections are kept in the pool and
+/// reused by the Browser and all its Elements. The pooled connections closed by the server
+/// are dropped before sending. If a pooled connection breaks anyway, the request is resent
+/// over a new one, unless it is a POST which was already written: the server may have executed it.
+#[derive(Debug)]
+pub struct TcpTransport {
+    ip: String,
+    port: String,
+    max_idle_connections: usize,
+    read_timeout: Option<Duration>,
+    pool: Mutex<Vec<BufReader<TcpStream>>>,
+}
+impl TcpTransport {
+    pub fn new(ip: &str, port: &str) -> Self {
+        TcpTransport {
+            ip: String::from(ip),
+            port: String::from(port),
+            max_idle_connections: 4,
+            read_timeout: None,
+            pool: Mutex::new(vec![]),
+        }
+    }
+    ///Zero disables the pooling, so every request opens a new connection
+    pub fn set_max_idle_connections(&mut self, max_idle_connections: usize) -> &mut Self {
+        self.max_idle_connections = max_idle_connections;
+        self
+    }
+    ///How long to wait for the response before failing with the Transport error.
+    /// None (the default) waits forever, e.g. for the long running scripts.
+    pub fn set_read_timeout(&mut self, read_timeout: Option<Duration>) -> &mut Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+    fn connect(&self) -> Result<BufReader<TcpStream>, WebDriverError> {
+        let address = format!("{}:{}", self.ip, self.port);
+        let connection = TcpStream::connect(address)?;
+        connection.set_nodelay(true)?;
+        connection.set_read_timeout(self.read_timeout)?;
+        Ok(BufReader::new(connection))
+    }
+    //Skips the idle connections the server has closed meanwhile
+    fn take_idle(&self) -> Option<BufReader<TcpStream>> {
+        let mut pool = self.pool.lock().unwrap();
+        while let Some(connection) = pool.pop() {
+            if is_open(&connection) {
+                return Some(connection);
+            }
+        }
+        None
+    }
+    fn release(&self, connection: BufReader<TcpStream>) {
+        let mut pool = self.pool.lock().unwrap();
+        if pool.len() < self.max_idle_connections {
+            pool.push(connection);
+        }
+    }
+}
+impl Transport for TcpTransport {
+    fn send(&self, method: Method, path: &str, body: &str) -> Result<HttpResponse, WebDriverError> {
+        let headers = match method {
+            Method::POST => cont_length_header(body),
+            _ => vec![],
+        };
+        let host = format!("{}:{}", self.ip, self.port);
+        let request = create_req(method, &host, path, headers, body);
+        loop {
+            let pooled = self.take_idle();
+            let reused = pooled.is_some();
+            let mut connection = match pooled {
+                Some(connection) => connection,
+                None => self.connect()?,
+            };
+            //The request which could not be written was not executed, so it is safe to send it again
+            match write_request(&mut connection, &request) {
+                Err(WebDriverError::Transport(_)) if reused => continue,
+                Err(e) => return Err(e),
+                Ok(()) => (),
+            }
+            match read_response(&mut connection) {
+                Ok(response) => {
+                    if reusable(&response) {
+                        self.release(connection);
+                    }
+                    return Ok(response);
+                }
+                //The server may have closed the idle connection without reading the request,
+                //but it may also have executed it, so a POST (e.g. element/click) is not repeated
+                Err(WebDriverError::Transport(_)) if reused && method != Method::POST => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+fn write_request(
+    connection: &mut BufReader<TcpStream>,
+    request: &str,
+) -> Result<(), WebDriverError> {
+    let stream = connection.get_mut();
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+//The connection closed by the server reads as the end of the stream without blocking
+fn is_open(connection: &BufReader<TcpStream>) -> bool {
+    let stream = connection.get_ref();
+    if !connection.buffer().is_empty() || stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let mut byte = [0; 1];
+    let open =
+        matches!(stream.peek(&mut byte), Err(e) if e.kind() == std::io::ErrorKind::WouldBlock);
+    stream.set_nonblocking(false).is_ok() && open
+}
+///The connection can only be reused if the server keeps it open and the body length was known
+pub(crate) fn reusable(response: &HttpResponse) -> bool {
+    let closes = response
+        .header("Connection")
+        .is_some_and(|v| v.eq_ignore_ascii_case("close"));
+    let delimited = response.header("Content-Length").is_some()
+        || response.header("Transfer-Encoding").is_some()
+        || response.status == 204
+        || response.status == 304;
+    !closes && delimited
+}
+
+///The transport of one session, shared by the Browser and its Elements
+///
+/// Once the session is closed, every request fails with WebDriverError::SessionClosed
+/// instead of being sent to the server. If the wire observer is set, it is notified
+/// about every request sent through the session.
+#[derive(Debug)]
+pub(crate) struct SessionTransport<T: ?Sized> {
+    inner: Arc<T>,
+    closed: AtomicBool,
+    local: AtomicBool,
+    observer: RwLock<Option<Arc<dyn WireObserver>>>,
+}
+impl<T: ?Sized> SessionTransport<T> {
+    pub(crate) fn new(inner: Arc<T>) -> Self {
+        SessionTransport {
+            inner,
+            closed: AtomicBool::new(false),
+            local: AtomicBool::new(false),
+            observer: RwLock::new(None),
+        }
+    }
+    pub(crate) fn set_observer(&self, observer: Arc<dyn WireObserver>) {
+        *self.observer.write().unwrap() = Some(observer);
+    }
+    pub(crate) fn observer(&self) -> Option<Arc<dyn WireObserver>> {
+        self.observer.read().unwrap().clone()
+    }
+    pub(crate) fn inner(&self) -> Result<&T, WebDriverError> {
+        if self.is_closed() {
+            return Err(WebDriverError::SessionClosed);
+        }
+        Ok(&self.inner)
+    }
+    ///The underlying transport, bypassing the closed check
+    pub(crate) fn shared_inner(&self) -> Arc<T> {
+        self.inner.clone()
+    }
+    pub(crate) fn set_local(&self, local: bool) {
+        self.local.store(local, Ordering::SeqCst);
+    }
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+impl Transport for SessionTransport<dyn Transport> {
+    fn send(&self, method: Method, path: &str, body: &str) -> Result<HttpResponse, WebDriverError> {
+        let inner = self.inner()?;
+        let observer = match self.observer() {
+            Some(observer) => observer,
+            None => return inner.send(method, path, body),
+        };
+        observer.on_request(method, path, body);
+        let started = Instant::now();
+        let result = inner.send(method, path, body);
+        observer.on_response(method, path, &result, started.elapsed());
+        result
+    }
+    fn is_local(&self) -> bool {
+        self.local.load(Ordering::SeqCst) || self.inner.is_local()
+    }
+}
+
+///A request received by the MockTransport
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) body: String,
+}
+impl RecordedRequest {
+    pub fn get_method(&self) -> Method {
+        self.method
+    }
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+    pub fn get_body(&self) -> &str {
+        &self.body
+    }
+}
+#[derive(Debug)]
+struct Expectation {
+    method: Method,
+    path: String,
+    status: u16,
+    response: String,
+}
+#[derive(Debug, Default)]
+struct MockState {
+    expectations: VecDeque<Expectation>,
+    requests: Vec<RecordedRequest>,
+    local: bool,
+}
+///In-memory transport answering the requests with scripted responses
+///
+/// The expectations are matched in the order they were added. A request which does not
+/// match the next expectation gets an InvalidResponse error. The clones share the state,
+/// so one clone can be given to the Browser while the other one is used for assertions.
+/// # Examples
+/// ```
+/// # use selenium_webdriver::*;
+/// let mock = MockTransport::new();
+/// mock.expect(Method::POST, "wd/hub/session", r#"{"value":{"sessionId":"1","capabilities":{}}}"#)
+///     .expect(Method::GET, "wd/hub/session/1/title", r#"{"value":"Home"}"#);
+/// let c = Capabilities::new(BrowserName::Chrome, "linux");
+/// let br = Browser::start_session_with_transport(mock.clone(), c).unwrap();
+/// assert_eq!(br.get_title().unwrap(), "Home");
+/// assert!(mock.is_done());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+impl MockTransport {
+    pub fn new() -> Self {
+        MockTransport::default()
+    }
+    ///Queues a successful (200) response for the request with the given method and path
+    pub fn expect(&self, method: Method, path: &str, response: &str) -> &Self {
+        self.expect_status(method, path, 200, response)
+    }
+    ///Queues a response with an arbitrary status code, e.g. 404 with a W3C error payload
+    pub fn expect_status(&self, method: Method, path: &str, status: u16, response: &str) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .expectations
+            .push_back(Expectation {
+                method,
+                path: String::from(path),
+                status,
+                response: String::from(response),
+            });
+        self
+    }
+    ///All the requests received so far, including the unexpected ones
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+    ///Returns true if all the queued responses have been sent
+    pub fn is_done(&self) -> bool {
+        self.state.lock().unwrap().expectations.is_empty()
+    }
+    ///Makes the mock pretend the server runs on this machine, false by default
+    pub fn set_local(&self, local: bool) -> &Self {
+        self.state.lock().unwrap().local = local;
+        self
+    }
+}
+impl Transport for MockTransport {
+    fn send(&self, method: Method, path: &str, body: &str) -> Result<HttpResponse, WebDriverError> {
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method,
+            path: String::from(path),
+            body: String::from(body),
+        });
+        match state.expectations.front() {
+            Some(exp) if exp.method == method && exp.path == path => {
+                let exp = state.expectations.pop_front().unwrap();
+                Ok(HttpResponse::new(
+                    exp.status,
+                    vec![(
+                        String::from("Content-Type"),
+                        String::from("application/json; charset=utf-8"),
+                    )],
+                    exp.response.into_bytes(),
+                ))
+            }
+            Some(exp) => Err(WebDriverError::InvalidResponse(format!(
+                "Unexpected request {:?} {}, expected {:?} {}",
+                method, path, exp.method, exp.path
+            ))),
+            None => Err(WebDriverError::InvalidResponse(format!(
+                "Unexpected request {:?} {}, no more responses queued",
+                method, path
+            ))),
+        }
+    }
+    fn is_local(&self) -> bool {
+        self.state.lock().unwrap().local
+    }
+}
+
+//The Browser of the tests, started on a fresh MockTransport with the session "s1" of the browser,
+//e.g. "firefox". It is not detached, so dropping it sends the DELETE request.
+#[cfg(test)]
+pub(crate) fn mock_browser(browser_name: &str) -> (MockTransport, Browser) {
+    let mock = MockTransport::new();
+    mock.expect(
+        Method::POST,
+        "wd/hub/session",
+        &format!(
+            r#"{{"value":{{"sessionId":"s1","capabilities":{{"browserName":"{}"}}}}}}"#,
+            browser_name
+        ),
+    );
+    let c = Capabilities::new(BrowserName::Chrome, "linux");
+    let br = Browser::start_session_with_transport(mock.clone(), c).unwrap();
+    (mock, br)
+}
+//The same for the AsyncBrowser
+#[cfg(all(test, feature = "async"))]
+pub(crate) async fn mock_async_browser(browser_name: &str) -> (MockTransport, AsyncBrowser) {
+    let mock = MockTransport::new();
+    mock.expect(
+        Method::POST,
+        "wd/hub/session",
+        &format!(
+            r#"{{"value":{{"sessionId":"s1","capabilities":{{"browserName":"{}"}}}}}}"#,
+            browser_name
+        ),
+    );
+    let c = Capabilities::new(BrowserName::Chrome, "linux");
+    let br = AsyncBrowser::start_session_with_transport(mock.clone(), c)
+        .await
+        .unwrap();
+    (mock, br)
+}
+
+mod transport_tests {
+    use super::*;
+    use std::io::{BufRead, Read};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    //Reads one request, returns false if the client closed the connection
+    fn read_request(reader: &mut BufReader<TcpStream>) -> bool {
+        let mut length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return false;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(l) = line.strip_prefix("Content-Length: ") {
+                length = l.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        true
+    }
+    //Serves "{"value":<n>}" where n is the number of the request on the connection.
+    //If close_after_response is set, the server drops the connection without telling the client.
+    //The connection is also dropped without an answer on the request number drop_at.
+    //Returns the number of the accepted connections and of the received requests.
+    fn serve(
+        close_after_response: bool,
+        drop_at: usize,
+    ) -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port().to_string();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let received = Arc::new(AtomicUsize::new(0));
+        let (accepted_counter, received_counter) = (accepted.clone(), received.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                accepted_counter.fetch_add(1, Ordering::SeqCst);
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut n = 0;
+                while read_request(&mut reader) {
+                    n += 1;
+                    if received_counter.fetch_add(1, Ordering::SeqCst) + 1 == drop_at {
+                        break;
+                    }
+                    let resp_body = format!(r#"{{"value":{}}}"#, n);
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        resp_body.len(),
+                        resp_body
+                    );
+                    reader.get_mut().write_all(resp.as_bytes()).unwrap();
+                    if close_after_response {
+                        break;
+                    }
+                }
+            }
+        });
+        (port, accepted, received)
+    }
+    #[test]
+    fn keep_alive_reuses_connection() {
+        let (port, accepted, _) = serve(false, 0);
+        let transport = TcpTransport::new("127.0.0.1", &port);
+        for i in 1..=3 {
+            let resp = transport.send(Method::POST, "x", r#"{"a":1}"#).unwrap();
+            assert_eq!(resp.get_body(), format!(r#"{{"value":{}}}"#, i).as_bytes());
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+    #[test]
+    fn reconnects_on_closed_connection() {
+        let (port, accepted, _) = serve(true, 0);
+        let transport = TcpTransport::new("127.0.0.1", &port);
+        for _ in 0..3 {
+            let resp = transport.send(Method::GET, "x", "").unwrap();
+            assert_eq!(resp.get_body(), br#"{"value":1}"#);
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 3);
+    }
+    #[test]
+    fn post_is_not_repeated_after_it_was_sent() {
+        let (port, _, received) = serve(false, 2);
+        let transport = TcpTransport::new("127.0.0.1", &port);
+        transport.send(Method::POST, "x", "{}").unwrap();
+        let res = transport.send(Method::POST, "element/e1/click", "{}");
+        assert!(matches!(res, Err(WebDriverError::Transport(_))));
+        assert_eq!(received.load(Ordering::SeqCst), 2);
+        let resp = transport.send(Method::GET, "x", "").unwrap();
+        assert_eq!(resp.get_body(), br#"{"value":1}"#);
+    }
+    #[test]
+    fn get_is_repeated_on_broken_connection() {
+        let (port, accepted, received) = serve(false, 2);
+        let transport = TcpTransport::new("127.0.0.1", &port);
+        transport.send(Method::GET, "x", "").unwrap();
+        let resp = transport.send(Method::GET, "x", "").unwrap();
+        assert_eq!(resp.get_body(), br#"{"value":1}"#);
+        assert_eq!(received.load(Ordering::SeqCst), 3);
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+    #[test]
+    fn mock_in_order() {
+        let mock = MockTransport::new();
+        mock.expect(Method::GET, "a", r#"{"value":1}"#)
+            .expect_status(Method::POST, "b", 404, r#"{"value":2}"#);
+        let first = mock.send(Method::GET, "a", "").unwrap();
+        assert_eq!(first.get_status(), 200);
+        assert_eq!(first.get_body(), br#"{"value":1}"#);
+        assert!(mock.send(Method::GET, "b", "").is_err());
+        let second = mock.send(Method::POST, "b", "{}").unwrap();
+        assert_eq!(second.get_status(), 404);
+        assert!(mock.is_done());
+        assert!(mock.send(Method::GET, "c", "").is_err());
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[2].get_body(), "{}");
+    }
+}
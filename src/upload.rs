@@ -0,0 +1,209 @@
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+use std::path::Path;
+
+impl Element {
+    ///Puts the file into the <input type=file> element
+    ///
+    /// By default the file is zipped and sent to the Selenium server with the "se/file" command first,
+    /// and the path the server saved it to is typed into the input. If the browser runs on this machine
+    /// (see Browser::set_local_file_detector), the absolute local path is typed instead.
+    /// The plain drivers (e.g. chromedriver) do not support "se/file", so the remote
+    /// uploads need the Selenium server or Grid.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use selenium_webdriver::*;
+    /// let br = Browser::start_remote_session(BrowserName::Chrome, "linux", "10.0.0.5", "4444").unwrap();
+    /// br.open("https://the-internet.herokuapp.com/upload").unwrap();
+    /// let input = br.find_element(By::id("file-upload")).unwrap();
+    /// input.upload_file("report.pdf").unwrap();
+    /// ```
+    pub fn upload_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WebDriverError> {
+        self.upload_files(&[path])
+    }
+    ///Puts several files into the <input type=file multiple> element, see upload_file
+    pub fn upload_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<(), WebDriverError> {
+        let mut input_paths = vec![];
+        for path in paths {
+            let path = path.as_ref();
+            let input_path = if self.transport.is_local() {
+                let absolute = std::fs::canonicalize(path).map_err(WebDriverError::Io)?;
+                absolute.to_string_lossy().into_owned()
+            } else {
+                self.send_file(path)?
+            };
+            input_paths.push(input_path);
+        }
+        self.send_keys(&input_paths.join("\n"))
+    }
+    //Returns the path of the file on the server
+    fn send_file(&self, path: &Path) -> Result<String, WebDriverError> {
+        let content = std::fs::read(path).map_err(WebDriverError::Io)?;
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => {
+                return Err(WebDriverError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} is not a file", path.display()),
+                )))
+            }
+        };
+        let zipped = zip_single_file(&name, &content)?;
+        let body = serde_json::json!({ "file": base64::encode(zipped) }).to_string();
+        let url = format!("{}/se/file", self.session_url());
+        let resp = send_command(&*self.transport, Method::POST, &url, &body)?;
+        Ok(serde_json::from_value(resp)?)
+    }
+}
+
+//The zip archive with one stored (not compressed) entry, which is what the "se/file" command expects
+pub(crate) fn zip_single_file(name: &str, content: &[u8]) -> Result<Vec<u8>, WebDriverError> {
+    if content.len() > u32::MAX as usize {
+        return Err(WebDriverError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "The files over 4 GB can not be uploaded",
+        )));
+    }
+    let crc = crc32(content);
+    let size = content.len() as u32;
+    let name = name.as_bytes();
+    //Version 2.0, no flags, stored, 00:00 01.01.1980
+    let mut entry = vec![];
+    entry.extend_from_slice(&20u16.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&0x21u16.to_le_bytes());
+    entry.extend_from_slice(&crc.to_le_bytes());
+    entry.extend_from_slice(&size.to_le_bytes());
+    entry.extend_from_slice(&size.to_le_bytes());
+    entry.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+
+    let mut zip = vec![];
+    zip.extend_from_slice(&0x04034b50u32.to_le_bytes());
+    zip.extend_from_slice(&entry);
+    zip.extend_from_slice(name);
+    zip.extend_from_slice(content);
+
+    let central_offset = zip.len() as u32;
+    zip.extend_from_slice(&0x02014b50u32.to_le_bytes());
+    zip.extend_from_slice(&20u16.to_le_bytes());
+    zip.extend_from_slice(&entry);
+    //No comment, disk 0, no attributes, the local header is at the start
+    zip.extend_from_slice(&[0; 14]);
+    zip.extend_from_slice(name);
+    let central_size = zip.len() as u32 - central_offset;
+
+    zip.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    zip.extend_from_slice(&[0, 0, 0, 0]);
+    zip.extend_from_slice(&1u16.to_le_bytes());
+    zip.extend_from_slice(&1u16.to_le_bytes());
+    zip.extend_from_slice(&central_size.to_le_bytes());
+    zip.extend_from_slice(&central_offset.to_le_bytes());
+    zip.extend_from_slice(&0u16.to_le_bytes());
+    Ok(zip)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+mod upload_tests {
+    use super::*;
+    use crate::browser::*;
+    use crate::transport::*;
+    #[cfg(test)]
+    fn mock_input() -> (MockTransport, Browser, Element) {
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"in"}}"#,
+        );
+        let input = br.find_element(LocatorStrategy::CSS("input")).unwrap();
+        (mock, br, input)
+    }
+    #[test]
+    fn stored_zip() {
+        assert_eq!(crc32(b"hello"), 0x3610_a686);
+        let zip = zip_single_file("a.txt", b"hello").unwrap();
+        assert_eq!(&zip[..4], b"PK\x03\x04");
+        assert_eq!(&zip[30..35], b"a.txt");
+        assert_eq!(&zip[35..40], b"hello");
+        assert_eq!(&zip[40..44], b"PK\x01\x02");
+        assert_eq!(zip.len(), 30 + 5 + 5 + 46 + 5 + 22);
+        let eocd = &zip[zip.len() - 22..];
+        assert_eq!(&eocd[..4], b"PK\x05\x06");
+        assert_eq!(&eocd[16..20], &40u32.to_le_bytes());
+    }
+    #[test]
+    fn uploads_to_remote_server() {
+        //The process id keeps the concurrent test runs apart
+        let name = format!("selenium_webdriver_upload_test_{}.txt", std::process::id());
+        let path = std::env::temp_dir().join(&name);
+        std::fs::write(&path, "report").unwrap();
+        let (mock, _br, input) = mock_input();
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/se/file",
+            r#"{"value":"/tmp/upload1/selenium_webdriver_upload_test.txt"}"#,
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/element/in/value",
+            r#"{"value":null}"#,
+        );
+        input.upload_file(&path).unwrap();
+        assert!(mock.is_done());
+        let requests = mock.requests();
+        let body: serde_json::Value = serde_json::from_str(requests[2].get_body()).unwrap();
+        let zip = base64::decode(body["file"].as_str().unwrap()).unwrap();
+        assert_eq!(zip, zip_single_file(&name, b"report").unwrap());
+        assert_eq!(
+            requests[3].get_body(),
+            r#"{"text":"/tmp/upload1/selenium_webdriver_upload_test.txt"}"#
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn types_local_paths() {
+        let first = std::env::temp_dir().join(format!(
+            "selenium_webdriver_local_{}_1.txt",
+            std::process::id()
+        ));
+        let second = std::env::temp_dir().join(format!(
+            "selenium_webdriver_local_{}_2.txt",
+            std::process::id()
+        ));
+        std::fs::write(&first, "1").unwrap();
+        std::fs::write(&second, "2").unwrap();
+        let (mock, mut br, input) = mock_input();
+        br.set_local_file_detector(true);
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element/in/value",
+            r#"{"value":null}"#,
+        );
+        input.upload_files(&[&first, &second]).unwrap();
+        let body: serde_json::Value = serde_json::from_str(mock.requests()[2].get_body()).unwrap();
+        let expected = format!(
+            "{}\n{}",
+            std::fs::canonicalize(&first).unwrap().display(),
+            std::fs::canonicalize(&second).unwrap().display()
+        );
+        assert_eq!(body["text"], expected.as_str());
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+use super::browser::*;
+use super::element::*;
+use super::error::*;
+use super::screenshot::*;
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+
+const CLIENT_RECTS_SCRIPT: &str = "var ratio = window.devicePixelRatio || 1; return Array.prototype.map.call(arguments, function(e) { var r = e.getBoundingClientRect(); return [r.left * ratio, r.top * ratio, r.width * ratio, r.height * ratio]; });";
+
+///A rectangle in the screenshot pixels, e.g. the area of a clock ignored by VisualCheck
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && (x - self.x) < self.width && (y - self.y) < self.height
+    }
+}
+
+///Compares the screenshots with the baseline images stored in a directory
+///
+/// The baseline of the check "name" is the file "name.png". If it does not exist, the screenshot
+/// is saved as the baseline and the check passes (unless set_create_baseline(false) is called).
+/// The pixels, whose channels differ from the baseline by no more than the tolerance, are equal.
+/// The check passes if no more than max_diff_pixels pixels differ, otherwise the screenshot
+/// "name.actual.png" and the diff "name.diff.png" with the differing pixels painted red
+/// are written to the diff directory (the baseline directory by default).
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless", "--window-size=1280,800"]);
+/// br.open("https://vk.com").unwrap();
+/// let clock = br.find_element(By::id("clock")).unwrap();
+/// let result = VisualCheck::new(&br, "tests/baselines")
+///     .set_tolerance(8)
+///     .set_max_diff_pixels(100)
+///     .ignore_region(Region::new(0, 0, 1280, 60))
+///     .ignore_element(&clock)
+///     .check_page("home")
+///     .unwrap();
+/// assert!(result.passed(), "{} pixels differ", result.get_diff_pixels());
+/// ```
+pub struct VisualCheck<'a> {
+    browser: &'a Browser,
+    baseline_dir: PathBuf,
+    diff_dir: Option<PathBuf>,
+    tolerance: u8,
+    max_diff_pixels: usize,
+    regions: Vec<Region>,
+    elements: Vec<&'a Element>,
+    create_baseline: bool,
+}
+impl<'a> VisualCheck<'a> {
+    ///No tolerance, no differing pixels allowed, the missing baselines are created
+    pub fn new<P: AsRef<Path>>(browser: &'a Browser, baseline_dir: P) -> Self {
+        VisualCheck {
+            browser,
+            baseline_dir: baseline_dir.as_ref().to_path_buf(),
+            diff_dir: None,
+            tolerance: 0,
+            max_diff_pixels: 0,
+            regions: vec![],
+            elements: vec![],
+            create_baseline: true,
+        }
+    }
+    ///The max difference of every channel (0-255) of the pixels considered equal
+    pub fn set_tolerance(&mut self, tolerance: u8) -> &mut Self {
+        self.tolerance = tolerance;
+        self
+    }
+    ///How many pixels may differ for the check to pass
+    pub fn set_max_diff_pixels(&mut self, max_diff_pixels: usize) -> &mut Self {
+        self.max_diff_pixels = max_diff_pixels;
+        self
+    }
+    ///The region is not compared. For the element checks it is relative to the element.
+    pub fn ignore_region(&mut self, region: Region) -> &mut Self {
+        self.regions.push(region);
+        self
+    }
+    ///The area of the element is not compared. It is located when the check runs,
+    /// with the device pixel ratio taken into account.
+    pub fn ignore_element(&mut self, element: &'a Element) -> &mut Self {
+        self.elements.push(element);
+        self
+    }
+    pub fn set_diff_dir<P: AsRef<Path>>(&mut self, diff_dir: P) -> &mut Self {
+        self.diff_dir = Some(diff_dir.as_ref().to_path_buf());
+        self
+    }
+    ///If false, the missing baseline is the Io error with the NotFound kind
+    pub fn set_create_baseline(&mut self, create_baseline: bool) -> &mut Self {
+        self.create_baseline = create_baseline;
+        self
+    }
+    ///Compares the screenshot of the viewport with the baseline "name.png"
+    pub fn check_page(&self, name: &str) -> Result<VisualResult, WebDriverError> {
+        let actual = self.browser.get_screenshot_image()?;
+        let ignored = self.ignored_regions(None)?;
+        self.check(name, actual, &ignored)
+    }
+    ///Compares the screenshot of the element with the baseline "name.png"
+    pub fn check_element(
+        &self,
+        element: &Element,
+        name: &str,
+    ) -> Result<VisualResult, WebDriverError> {
+        let actual = self.browser.get_element_screenshot_image(element)?;
+        let ignored = self.ignored_regions(Some(element))?;
+        self.check(name, actual, &ignored)
+    }
+    fn check(
+        &self,
+        name: &str,
+        actual: RgbaImage,
+        ignored: &[Region],
+    ) -> Result<VisualResult, WebDriverError> {
+        let baseline_path = self.baseline_dir.join(format!("{}.png", name));
+        if !baseline_path.exists() {
+            if !self.create_baseline {
+                return Err(WebDriverError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("The baseline {} does not exist", baseline_path.display()),
+                )));
+            }
+            std::fs::create_dir_all(&self.baseline_dir).map_err(WebDriverError::Io)?;
+            let png = encode_image(&actual, ScreenshotFormat::Png)?;
+            std::fs::write(&baseline_path, png).map_err(WebDriverError::Io)?;
+            return Ok(VisualResult {
+                diff_pixels: 0,
+                max_diff_pixels: self.max_diff_pixels,
+                baseline_created: true,
+                diff_path: None,
+            });
+        }
+        let baseline = std::fs::read(&baseline_path).map_err(WebDriverError::Io)?;
+        let baseline = decode_png(&baseline)?;
+        let (diff_pixels, diff) = compare_images(&baseline, &actual, self.tolerance, ignored);
+        let mut diff_path = None;
+        if diff_pixels > self.max_diff_pixels {
+            let diff_dir = self.diff_dir.as_ref().unwrap_or(&self.baseline_dir);
+            std::fs::create_dir_all(diff_dir).map_err(WebDriverError::Io)?;
+            let actual_png = encode_image(&actual, ScreenshotFormat::Png)?;
+            let actual_path = diff_dir.join(format!("{}.actual.png", name));
+            std::fs::write(actual_path, actual_png).map_err(WebDriverError::Io)?;
+            let diff_png = encode_image(&diff, ScreenshotFormat::Png)?;
+            let path = diff_dir.join(format!("{}.diff.png", name));
+            std::fs::write(&path, diff_png).map_err(WebDriverError::Io)?;
+            diff_path = Some(path);
+        }
+        Ok(VisualResult {
+            diff_pixels,
+            max_diff_pixels: self.max_diff_pixels,
+            baseline_created: false,
+            diff_path,
+        })
+    }
+    //The ignored regions and elements in the screenshot pixels, relative to the origin element if any
+    fn ignored_regions(&self, origin: Option<&Element>) -> Result<Vec<Region>, WebDriverError> {
+        let mut regions = self.regions.clone();
+        if self.elements.is_empty() {
+            return Ok(regions);
+        }
+        let mut elements = self.elements.clone();
+        elements.extend(origin);
+        let mut rects: Vec<[f64; 4]> = self.browser.execute(CLIENT_RECTS_SCRIPT, &elements)?;
+        let (left, top) = match origin {
+            Some(_) => rects.pop().map(|r| (r[0], r[1])).unwrap_or_default(),
+            None => (0.0, 0.0),
+        };
+        for [x, y, width, height] in rects {
+            let (x, y) = (x - left, y - top);
+            let (right, bottom) = (x + width, y + height);
+            if right <= 0.0 || bottom <= 0.0 {
+                continue;
+            }
+            let (x, y) = (x.max(0.0).floor(), y.max(0.0).floor());
+            regions.push(Region::new(
+                x as u32,
+                y as u32,
+                (right.ceil() - x) as u32,
+                (bottom.ceil() - y) as u32,
+            ));
+        }
+        Ok(regions)
+    }
+}
+
+///The outcome of a VisualCheck
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct VisualResult {
+    diff_pixels: usize,
+    max_diff_pixels: usize,
+    baseline_created: bool,
+    diff_path: Option<PathBuf>,
+}
+impl VisualResult {
+    pub fn passed(&self) -> bool {
+        self.diff_pixels <= self.max_diff_pixels
+    }
+    ///The number of the differing pixels. If the sizes differ, the pixels present
+    /// in only one of the images are counted too.
+    pub fn get_diff_pixels(&self) -> usize {
+        self.diff_pixels
+    }
+    ///Whether there was no baseline and the screenshot became it
+    pub fn is_baseline_created(&self) -> bool {
+        self.baseline_created
+    }
+    ///The diff image written when the check failed
+    pub fn get_diff_path(&self) -> Option<&Path> {
+        self.diff_path.as_deref()
+    }
+}
+
+//Returns the number of the differing pixels and the diff image: the actual image (or the baseline
+//where there is no actual one) faded, with the differing pixels painted red
+pub(crate) fn compare_images(
+    baseline: &RgbaImage,
+    actual: &RgbaImage,
+    tolerance: u8,
+    ignored: &[Region],
+) -> (usize, RgbaImage) {
+    let width = baseline.width().max(actual.width());
+    let height = baseline.height().max(actual.height());
+    let mut diff = RgbaImage::new(width, height);
+    let mut diff_pixels = 0;
+    for (x, y, pixel) in diff.enumerate_pixels_mut() {
+        let expected = baseline.get_pixel_checked(x, y);
+        let got = actual.get_pixel_checked(x, y);
+        let background = got
+            .or(expected)
+            .copied()
+            .unwrap_or(Rgba([255, 255, 255, 255]));
+        *pixel = fade(background);
+        if ignored.iter().any(|r| r.contains(x, y)) {
+            continue;
+        }
+        let equal = match (expected, got) {
+            (Some(expected), Some(got)) => expected
+                .0
+                .iter()
+                .zip(got.0.iter())
+                .all(|(e, g)| e.abs_diff(*g) <= tolerance),
+            _ => false,
+        };
+        if !equal {
+            diff_pixels += 1;
+            *pixel = Rgba([255, 0, 0, 255]);
+        }
+    }
+    (diff_pixels, diff)
+}
+fn fade(pixel: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = pixel.0;
+    let fade = |c: u8| 255 - (255 - c) / 4;
+    Rgba([fade(r), fade(g), fade(b), 255])
+}
+
+mod visual_tests {
+    use super::*;
+    use crate::reqs::*;
+    use crate::transport::*;
+    fn plain(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([value, value, value, 255]))
+    }
+    #[test]
+    fn compares_pixels() {
+        let baseline = plain(4, 4, 100);
+        let mut actual = plain(4, 4, 100);
+        actual.put_pixel(0, 0, Rgba([105, 100, 100, 255]));
+        actual.put_pixel(3, 3, Rgba([200, 100, 100, 255]));
+        actual.put_pixel(2, 0, Rgba([0, 0, 0, 255]));
+        let (count, diff) = compare_images(&baseline, &actual, 5, &[]);
+        assert_eq!(count, 2);
+        assert_eq!(diff.get_pixel(3, 3), &Rgba([255, 0, 0, 255]));
+        assert_eq!(diff.get_pixel(0, 0), &fade(Rgba([105, 100, 100, 255])));
+        let (count, _) = compare_images(&baseline, &actual, 5, &[Region::new(2, 0, 1, 1)]);
+        assert_eq!(count, 1);
+        let (count, diff) = compare_images(&baseline, &plain(4, 5, 100), 0, &[]);
+        assert_eq!(count, 4);
+        assert_eq!(diff.dimensions(), (4, 5));
+    }
+    #[test]
+    fn creates_baseline_and_writes_diff() {
+        //The process id keeps the concurrent test runs apart
+        let dir = std::env::temp_dir().join(format!(
+            "selenium_webdriver_visual_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut changed = plain(4, 4, 100);
+        for x in 0..4 {
+            changed.put_pixel(x, 1, Rgba([0, 0, 0, 255]));
+        }
+        let screenshot = |image: &RgbaImage| {
+            let png = encode_image(image, ScreenshotFormat::Png).unwrap();
+            format!(r#"{{"value":"{}"}}"#, base64::encode(png))
+        };
+        let (mock, br) = mock_browser("chrome");
+        mock.expect(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"banner"}}"#,
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/screenshot",
+            &screenshot(&plain(4, 4, 100)),
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/screenshot",
+            &screenshot(&changed),
+        )
+        .expect(
+            Method::GET,
+            "wd/hub/session/s1/screenshot",
+            &screenshot(&changed),
+        )
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":[[-1.5,0.5,3.2,1.0]]}"#,
+        );
+        let banner = br.find_element(LocatorStrategy::CSS(".banner")).unwrap();
+
+        let mut check = VisualCheck::new(&br, &dir);
+        check.set_max_diff_pixels(1);
+        let result = check.check_page("home").unwrap();
+        assert!(result.passed() && result.is_baseline_created());
+        assert!(dir.join("home.png").exists());
+
+        let result = check.check_page("home").unwrap();
+        assert!(!result.passed());
+        assert_eq!(result.get_diff_pixels(), 4);
+        let diff = std::fs::read(result.get_diff_path().unwrap()).unwrap();
+        let diff = decode_png(&diff).unwrap();
+        assert_eq!(diff.get_pixel(2, 1), &Rgba([255, 0, 0, 255]));
+        assert!(dir.join("home.actual.png").exists());
+
+        //The banner covers the pixels 0-1 of the row 1, so the pixels 2 and 3 still differ
+        let result = check.ignore_element(&banner).check_page("home").unwrap();
+        assert_eq!(result.get_diff_pixels(), 2);
+        assert!(mock.is_done());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
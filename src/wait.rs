@@ -0,0 +1,362 @@
+use super::browser::*;
+use super::element::*;
+use super::error::*;
+use super::reqs::*;
+use std::time::{Duration, Instant};
+
+///Polls a condition until it is met or the timeout expires
+///
+/// The condition returns Ok(Some(value)) when it is met, Ok(None) to keep polling, or an error.
+/// The errors with the ignored codes ("no such element" by default) are treated like Ok(None),
+/// any other error stops the wait. When the timeout expires, the Timeout error is returned
+/// with the custom message (if set) and the last ignored error.
+///
+/// # Examples
+/// ```no_run
+/// # use selenium_webdriver::*;
+/// # use std::time::Duration;
+/// let br = Browser::start_session(BrowserName::Chrome, vec!["--headless"]);
+/// br.open("https://vk.com").unwrap();
+/// let button = br
+///     .wait()
+///     .set_timeout(Duration::from_secs(5))
+///     .set_message("the login button did not appear")
+///     .until(Condition::element_clickable(By::id("index_login_button")))
+///     .unwrap();
+/// button.click().unwrap();
+/// ```
+pub struct Wait<'a> {
+    browser: &'a Browser,
+    timeout: Duration,
+    polling: Duration,
+    ignored: Vec<String>,
+    message: Option<String>,
+}
+impl<'a> Wait<'a> {
+    ///10 seconds timeout, polling every 500 ms, ignoring "no such element"
+    pub fn new(browser: &'a Browser) -> Self {
+        Wait {
+            browser,
+            timeout: Duration::from_secs(10),
+            polling: Duration::from_millis(500),
+            ignored: vec![String::from("no such element")],
+            message: None,
+        }
+    }
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+    pub fn set_polling(&mut self, polling: Duration) -> &mut Self {
+        self.polling = polling;
+        self
+    }
+    ///Keeps polling if the condition fails with this W3C error code, e.g. "stale element reference"
+    pub fn ignore(&mut self, error_code: &str) -> &mut Self {
+        self.ignored.push(String::from(error_code));
+        self
+    }
+    ///The message of the Timeout error returned when the condition is not met in time
+    pub fn set_message(&mut self, message: &str) -> &mut Self {
+        self.message = Some(String::from(message));
+        self
+    }
+    pub fn until<T, F>(&self, mut condition: F) -> Result<T, WebDriverError>
+    where
+        F: FnMut(&Browser) -> Result<Option<T>, WebDriverError>,
+    {
+        let started = Instant::now();
+        let mut last_error = None;
+        loop {
+            match condition(self.browser) {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => (),
+                Err(e) if self.is_ignored(&e) => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+            let elapsed = started.elapsed();
+            if elapsed >= self.timeout {
+                return Err(self.timeout_error(last_error));
+            }
+            std::thread::sleep(self.polling.min(self.timeout - elapsed));
+        }
+    }
+    fn is_ignored(&self, error: &WebDriverError) -> bool {
+        match error.error_code() {
+            Some(code) => self.ignored.iter().any(|c| c == code),
+            None => false,
+        }
+    }
+    fn timeout_error(&self, last_error: Option<WebDriverError>) -> WebDriverError {
+        let mut message = match &self.message {
+            Some(message) => message.clone(),
+            None => format!("The condition was not met in {:?}", self.timeout),
+        };
+        if let Some(e) = last_error {
+            message.push_str(&format!(" (last error: {})", e));
+        }
+        WebDriverError::Timeout(ErrorDetails {
+            error: String::from("timeout"),
+            message,
+            stacktrace: String::new(),
+            data: None,
+        })
+    }
+}
+impl Browser {
+    ///Creates the Wait with the default settings, see the Wait docs
+    pub fn wait(&self) -> Wait<'_> {
+        Wait::new(self)
+    }
+}
+
+///The common conditions for the Wait::until
+///
+/// The conditions looking for an element treat a missing or stale element as not met yet,
+/// so they keep polling regardless of the ignored error codes.
+pub struct Condition;
+impl Condition {
+    ///The element is in the DOM
+    pub fn element_present<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+    ) -> impl FnMut(&Browser) -> Result<Option<Element>, WebDriverError> {
+        move |br| not_found_as_none(br.find_element(locator.to_owned_locator()))
+    }
+    ///The element is in the DOM and displayed
+    pub fn element_visible<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+    ) -> impl FnMut(&Browser) -> Result<Option<Element>, WebDriverError> {
+        move |br| {
+            let element = match not_found_as_none(br.find_element(locator.to_owned_locator()))? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            match not_found_as_none(element.is_displayed())? {
+                Some(true) => Ok(Some(element)),
+                _ => Ok(None),
+            }
+        }
+    }
+    ///The element is displayed and enabled
+    pub fn element_clickable<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+    ) -> impl FnMut(&Browser) -> Result<Option<Element>, WebDriverError> {
+        let mut visible = Condition::element_visible(locator);
+        move |br| {
+            let element = match visible(br)? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            match not_found_as_none(element.is_enabled())? {
+                Some(true) => Ok(Some(element)),
+                _ => Ok(None),
+            }
+        }
+    }
+    ///The element is either not in the DOM or not displayed
+    pub fn element_invisible<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+    ) -> impl FnMut(&Browser) -> Result<Option<()>, WebDriverError> {
+        move |br| {
+            let element = match not_found_as_none(br.find_element(locator.to_owned_locator()))? {
+                Some(element) => element,
+                None => return Ok(Some(())),
+            };
+            match not_found_as_none(element.is_displayed())? {
+                Some(true) => Ok(None),
+                _ => Ok(Some(())),
+            }
+        }
+    }
+    ///The text of the element contains the given text
+    pub fn text_present<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+        text: &str,
+    ) -> impl FnMut(&Browser) -> Result<Option<Element>, WebDriverError> {
+        let text = String::from(text);
+        move |br| {
+            let element = match not_found_as_none(br.find_element(locator.to_owned_locator()))? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            match not_found_as_none(element.get_element_text())? {
+                Some(t) if t.contains(&text) => Ok(Some(element)),
+                _ => Ok(None),
+            }
+        }
+    }
+    ///The attribute of the element has the given value
+    pub fn attribute_equals<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+        attribute: &str,
+        value: &str,
+    ) -> impl FnMut(&Browser) -> Result<Option<Element>, WebDriverError> {
+        let attribute = String::from(attribute);
+        let value = String::from(value);
+        move |br| {
+            let element = match not_found_as_none(br.find_element(locator.to_owned_locator()))? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            match not_found_as_none(element.get_attribute(&attribute))? {
+                Some(v) if v == value => Ok(Some(element)),
+                _ => Ok(None),
+            }
+        }
+    }
+    ///The page title contains the text, returns the whole title
+    pub fn title_contains(
+        text: &str,
+    ) -> impl FnMut(&Browser) -> Result<Option<String>, WebDriverError> {
+        let text = String::from(text);
+        move |br| {
+            let title = br.get_title()?;
+            Ok(Some(title).filter(|t| t.contains(&text)))
+        }
+    }
+    ///The current url contains the text, returns the whole url
+    pub fn url_contains(
+        text: &str,
+    ) -> impl FnMut(&Browser) -> Result<Option<String>, WebDriverError> {
+        let text = String::from(text);
+        Condition::url_matches(move |url| url.contains(&text))
+    }
+    ///The current url satisfies the predicate, returns the url
+    pub fn url_matches<P>(
+        predicate: P,
+    ) -> impl FnMut(&Browser) -> Result<Option<String>, WebDriverError>
+    where
+        P: Fn(&str) -> bool,
+    {
+        move |br| {
+            let url = br.get_link()?;
+            Ok(Some(url).filter(|u| predicate(u)))
+        }
+    }
+    ///There are exactly this many windows and tabs, returns their handles
+    pub fn number_of_windows(
+        count: usize,
+    ) -> impl FnMut(&Browser) -> Result<Option<Vec<String>>, WebDriverError> {
+        move |br| {
+            let handles = br.get_window_handles()?;
+            Ok(Some(handles).filter(|h| h.len() == count))
+        }
+    }
+    ///An alert is open, returns its text
+    pub fn alert_present() -> impl FnMut(&Browser) -> Result<Option<String>, WebDriverError> {
+        move |br| match br.get_alert_text() {
+            Ok(text) => Ok(Some(text)),
+            Err(WebDriverError::NoSuchAlert(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    ///The frame element is found and the browser is switched to it
+    pub fn frame_available_and_switch<S: AsRef<str>>(
+        locator: LocatorStrategy<S>,
+    ) -> impl FnMut(&Browser) -> Result<Option<()>, WebDriverError> {
+        move |br| {
+            let frame = match not_found_as_none(br.find_element(locator.to_owned_locator()))? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            match br.switch_to_frame_by_element(frame) {
+                Ok(()) => Ok(Some(())),
+                Err(WebDriverError::NoSuchFrame(_))
+                | Err(WebDriverError::StaleElementReference(_)) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+fn not_found_as_none<T>(result: Result<T, WebDriverError>) -> Result<Option<T>, WebDriverError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(WebDriverError::NoSuchElement(_)) | Err(WebDriverError::StaleElementReference(_)) => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+mod wait_tests {
+    use super::*;
+    use crate::by::*;
+    use crate::transport::*;
+    const NO_SUCH_ELEMENT: &str =
+        r#"{"value":{"error":"no such element","message":"","stacktrace":""}}"#;
+    #[test]
+    fn polls_until_visible() {
+        let (mock, br) = mock_browser("chrome");
+        let found = r#"{"value":{"element-6066-11e4-a52e-4f735466cecf":"e1"}}"#;
+        mock.expect_status(
+            Method::POST,
+            "wd/hub/session/s1/element",
+            404,
+            NO_SUCH_ELEMENT,
+        )
+        .expect(Method::POST, "wd/hub/session/s1/element", found)
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":false}"#,
+        )
+        .expect(Method::POST, "wd/hub/session/s1/element", found)
+        .expect(
+            Method::POST,
+            "wd/hub/session/s1/execute/sync",
+            r#"{"value":true}"#,
+        );
+        let el = br
+            .wait()
+            .set_polling(Duration::from_millis(1))
+            .until(Condition::element_visible(By::id("a")))
+            .unwrap();
+        assert_eq!(el.element_id(), "e1");
+        assert!(mock.is_done());
+        let body: serde_json::Value = serde_json::from_str(mock.requests()[3].get_body()).unwrap();
+        assert_eq!(body["args"][0]["element-6066-11e4-a52e-4f735466cecf"], "e1");
+        assert!(body["script"].as_str().unwrap().contains("isShown"));
+    }
+    #[test]
+    fn times_out_with_message() {
+        let (mock, br) = mock_browser("chrome");
+        for _ in 0..100 {
+            mock.expect(
+                Method::GET,
+                "wd/hub/session/s1/title",
+                r#"{"value":"Loading"}"#,
+            );
+        }
+        let err = br
+            .wait()
+            .set_timeout(Duration::from_millis(20))
+            .set_polling(Duration::from_millis(5))
+            .set_message("no dashboard")
+            .until(Condition::title_contains("Dashboard"))
+            .unwrap_err();
+        assert!(matches!(err, WebDriverError::Timeout(_)));
+        assert_eq!(err.details().unwrap().get_message(), "no dashboard");
+    }
+    #[test]
+    fn ignored_and_fatal_errors() {
+        let (mock, br) = mock_browser("chrome");
+        let stale = r#"{"value":{"error":"stale element reference","message":"","stacktrace":""}}"#;
+        let closed = r#"{"value":{"error":"no such window","message":"","stacktrace":""}}"#;
+        mock.expect_status(Method::GET, "wd/hub/session/s1/url", 404, stale)
+            .expect(
+                Method::GET,
+                "wd/hub/session/s1/url",
+                r#"{"value":"http://a/done"}"#,
+            )
+            .expect_status(Method::GET, "wd/hub/session/s1/url", 404, closed);
+        let url = br
+            .wait()
+            .set_polling(Duration::from_millis(1))
+            .ignore("stale element reference")
+            .until(Condition::url_contains("done"))
+            .unwrap();
+        assert_eq!(url, "http://a/done");
+        let err = br.wait().until(Condition::url_contains("x")).unwrap_err();
+        assert!(matches!(err, WebDriverError::NoSuchWindow(_)));
+    }
+}
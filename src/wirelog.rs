@@ -0,0 +1,261 @@
+use super::error::*;
+use super::reqs::*;
+use serde_json::Value;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+///Receives every request the Browser and its Elements send to the server and every response
+///
+/// Set it with Browser::set_wire_observer. Both methods do nothing by default,
+/// so an implementation may override only the one it needs.
+pub trait WireObserver: Send + Sync + std::fmt::Debug {
+    ///Called before the request is sent. The path has no leading slash.
+    fn on_request(&self, _method: Method, _path: &str, _body: &str) {}
+    ///Called when the response is received or the request failed, with the time it took
+    fn on_response(
+        &self,
+        _method: Method,
+        _path: &str,
+        _response: &Result<HttpResponse, WebDriverError>,
+        _elapsed: Duration,
+    ) {
+    }
+}
+
+///Writes the transcript of the wire traffic to a file
+///
+/// The values of the sensitive json keys are replaced with "***" and long strings
+/// (e.g. base64 screenshots) are shortened. By default "text" (typed keys and alert text),
+/// "password" and "cookie" are redacted, more keys can be added with redact_key().
+/// The values of the cookies (the objects with the "name" and "value" keys) and of the keyDown
+/// and keyUp actions are always redacted.
+///
+/// # Examples
+/// ```
+/// # use selenium_webdriver::*;
+/// # let mock = MockTransport::new();
+/// # mock.expect(Method::POST, "wd/hub/session", r#"{"value":{"sessionId":"1","capabilities":{}}}"#)
+/// #    .expect(Method::GET, "wd/hub/session/1/title", r#"{"value":"Home"}"#);
+/// # let c = Capabilities::new(BrowserName::Chrome, "linux");
+/// let path = std::env::temp_dir().join(format!("wire_{}.log", std::process::id()));
+/// let mut logger = FileWireLogger::new(&path).unwrap();
+/// logger.redact_key("token");
+/// # let mut br = Browser::start_session_with_transport(mock, c).unwrap();
+/// br.set_wire_observer(logger);
+/// br.get_title().unwrap();
+/// let log = std::fs::read_to_string(&path).unwrap();
+/// assert!(log.contains("--> GET wd/hub/session/1/title"));
+/// # br.detach();
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FileWireLogger {
+    file: Mutex<File>,
+    started: Instant,
+    redacted_keys: Vec<String>,
+    max_value_len: usize,
+}
+impl FileWireLogger {
+    ///Creates the file, truncating it if it exists
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, WebDriverError> {
+        let file = File::create(path).map_err(WebDriverError::Io)?;
+        Ok(FileWireLogger {
+            file: Mutex::new(file),
+            started: Instant::now(),
+            redacted_keys: vec![
+                String::from("text"),
+                String::from("password"),
+                String::from("cookie"),
+            ],
+            max_value_len: 256,
+        })
+    }
+    ///The value of this json key will be replaced with "***" wherever it is found
+    pub fn redact_key(&mut self, key: &str) -> &mut Self {
+        self.redacted_keys.push(String::from(key));
+        self
+    }
+    ///Strings longer than this are cut in the transcript, 256 by default
+    pub fn set_max_value_len(&mut self, max_value_len: usize) -> &mut Self {
+        self.max_value_len = max_value_len;
+        self
+    }
+    fn write_line(&self, line: &str) {
+        let at = self.started.elapsed();
+        let line = format!("[{:>4}.{:03}] {}\n", at.as_secs(), at.subsec_millis(), line);
+        //The logging must never fail the command, so the write errors are ignored
+        let _ = self.file.lock().unwrap().write_all(line.as_bytes());
+    }
+    pub(crate) fn redact_body(&self, body: &[u8], is_response: bool) -> String {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Object(mut map)) if is_response => {
+                //The top level "value" of the response is the envelope, not a secret
+                if let Some(value) = map.get_mut("value") {
+                    self.redact(value);
+                }
+                Value::Object(map).to_string()
+            }
+            Ok(mut value) => {
+                self.redact(&mut value);
+                value.to_string()
+            }
+            Err(_) => self.shorten(&String::from_utf8_lossy(body)),
+        }
+    }
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                //A cookie, e.g. in the response of get_all_cookies or get_cookie,
+                //or a typed key of the actions
+                let is_cookie = map.contains_key("name") && map.contains_key("value");
+                let is_key = matches!(
+                    map.get("type").and_then(|t| t.as_str()),
+                    Some("keyDown") | Some("keyUp")
+                );
+                for (key, value) in map.iter_mut() {
+                    if (is_cookie || is_key) && key == "value" {
+                        *value = Value::from("***");
+                        continue;
+                    }
+                    if self.redacted_keys.iter().any(|k| k == key) {
+                        *value = Value::from("***");
+                    } else {
+                        self.redact(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|v| self.redact(v)),
+            Value::String(s) => *s = self.shorten(s),
+            _ => (),
+        }
+    }
+    fn shorten(&self, s: &str) -> String {
+        match s.char_indices().nth(self.max_value_len) {
+            Some((cut, _)) => format!("{}...({} chars)", &s[..cut], s.chars().count()),
+            None => String::from(s),
+        }
+    }
+}
+impl WireObserver for FileWireLogger {
+    fn on_request(&self, method: Method, path: &str, body: &str) {
+        let line = format!(
+            "--> {:?} {} {}",
+            method,
+            path,
+            self.redact_body(body.as_bytes(), false)
+        );
+        self.write_line(line.trim_end());
+    }
+    fn on_response(
+        &self,
+        method: Method,
+        path: &str,
+        response: &Result<HttpResponse, WebDriverError>,
+        elapsed: Duration,
+    ) {
+        let outcome = match response {
+            Ok(resp) => format!(
+                "{} {}",
+                resp.get_status(),
+                self.redact_body(resp.get_body(), true)
+            ),
+            Err(e) => format!("failed: {}", e),
+        };
+        self.write_line(&format!(
+            "<-- {:?} {} ({} ms) {}",
+            method,
+            path,
+            elapsed.as_millis(),
+            outcome
+        ));
+    }
+}
+
+mod wire_log_tests {
+    use super::*;
+    #[test]
+    fn redacts_and_shortens() {
+        let path = std::env::temp_dir().join(format!(
+            "selenium_webdriver_redact_test_{}.log",
+            std::process::id()
+        ));
+        let mut logger = FileWireLogger::new(&path).unwrap();
+        logger.set_max_value_len(4);
+        let request = logger.redact_body(br#"{"text":"hunter2","url":"abcdefgh"}"#, false);
+        assert_eq!(request, r#"{"text":"***","url":"abcd...(8 chars)"}"#);
+        let response = logger.redact_body(br#"{"value":{"password":"x"}}"#, true);
+        assert_eq!(response, r#"{"value":{"password":"***"}}"#);
+        let response = logger.redact_body(br#"{"value":"abc"}"#, true);
+        assert_eq!(response, r#"{"value":"abc"}"#);
+        assert_eq!(
+            logger.redact_body(b"not json at all", false),
+            "not ...(15 chars)"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn writes_transcript() {
+        let path = std::env::temp_dir().join(format!(
+            "selenium_webdriver_transcript_test_{}.log",
+            std::process::id()
+        ));
+        let logger = FileWireLogger::new(&path).unwrap();
+        logger.on_request(Method::POST, "a/value", r#"{"text":"secret"}"#);
+        let resp = HttpResponse::new(404, vec![], br#"{"value":{"error":"x"}}"#.to_vec());
+        logger.on_response(Method::POST, "a/value", &Ok(resp), Duration::from_millis(7));
+        logger.on_response(
+            Method::GET,
+            "a",
+            &Err(WebDriverError::SessionClosed),
+            Duration::from_millis(0),
+        );
+        let log = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert!(lines[0].ends_with(r#"--> POST a/value {"text":"***"}"#));
+        assert!(lines[1].ends_with(r#"<-- POST a/value (7 ms) 404 {"value":{"error":"x"}}"#));
+        assert!(lines[2].ends_with("<-- GET a (0 ms) failed: the session is closed"));
+        assert!(!log.contains("secret"));
+        std::fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn redacts_cookies() {
+        let path = std::env::temp_dir().join(format!(
+            "selenium_webdriver_cookie_log_test_{}.log",
+            std::process::id()
+        ));
+        let logger = FileWireLogger::new(&path).unwrap();
+        let all = logger.redact_body(
+            br#"{"value":[{"domain":"example.com","httpOnly":true,"name":"sid","path":"/","secure":false,"value":"abc123"}]}"#,
+            true,
+        );
+        assert_eq!(
+            all,
+            r#"{"value":[{"domain":"example.com","httpOnly":true,"name":"sid","path":"/","secure":false,"value":"***"}]}"#
+        );
+        let one = logger.redact_body(br#"{"value":{"name":"sid","value":"abc123"}}"#, true);
+        assert_eq!(one, r#"{"value":{"name":"sid","value":"***"}}"#);
+        let added = logger.redact_body(br#"{"cookie":{"name":"sid","value":"abc123"}}"#, false);
+        assert_eq!(added, r#"{"cookie":"***"}"#);
+        std::fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn redacts_typed_keys() {
+        let path = std::env::temp_dir().join(format!(
+            "selenium_webdriver_actions_log_test_{}.log",
+            std::process::id()
+        ));
+        let logger = FileWireLogger::new(&path).unwrap();
+        let actions = logger.redact_body(
+            br#"{"actions":[{"actions":[{"type":"keyDown","value":"s"},{"type":"keyUp","value":"s"},{"duration":0,"type":"pause"}],"id":"kb","type":"key"},{"actions":[{"button":0,"type":"pointerDown"}],"id":"mouse","type":"pointer"}]}"#,
+            false,
+        );
+        assert_eq!(
+            actions,
+            r#"{"actions":[{"actions":[{"type":"keyDown","value":"***"},{"type":"keyUp","value":"***"},{"duration":0,"type":"pause"}],"id":"kb","type":"key"},{"actions":[{"button":0,"type":"pointerDown"}],"id":"mouse","type":"pointer"}]}"#
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}